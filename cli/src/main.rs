@@ -19,8 +19,15 @@ struct ExpoPushCli {
     /// body in the push notification
     body: Option<String>,
 
-    #[structopt(short = "s", long = "sound")]
-    sound: bool,
+    #[structopt(
+        short = "s",
+        long = "sound",
+        value_name = "sound",
+        require_equals = true
+    )]
+    /// sound in the push notification: `default` when no value is given, a bundled file
+    /// name, or a JSON object like `{"critical":true,"volume":0.5}`
+    sound: Option<Option<Sound>>,
 
     #[structopt(long = "ttl", value_name = "seconds")]
     /// ttl in the push notification
@@ -67,8 +74,8 @@ async fn main() {
         msg = msg.body(body);
     }
 
-    if cli.sound {
-        msg = msg.sound(Sound::Default);
+    if let Some(sound) = cli.sound {
+        msg = msg.sound(sound.unwrap_or_default());
     }
 
     if let Some(ttl) = cli.ttl {
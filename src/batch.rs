@@ -0,0 +1,129 @@
+use std::ops::Range;
+
+use crate::{error::ExpoNotificationError, message::PushToken, response::PushTicket};
+
+/// The outcome of sending one chunk of messages.
+#[derive(Debug)]
+pub struct ChunkResult {
+    /// The positions, in the input of the send, of the messages in this chunk. A message
+    /// with more than `push_chunk_size` recipients is split across chunks, so the ranges of
+    /// consecutive chunks may overlap.
+    pub messages: Range<usize>,
+
+    /// The recipients of the chunk, in the order of the tickets, each with the position of
+    /// its message.
+    pub recipients: Vec<(usize, PushToken)>,
+
+    /// The tickets for the recipients of the chunk, in order, or the error that made the
+    /// chunk fail. A failed chunk was not accepted by the push notification server, except
+    /// for the recipients with a ticket in a [`PartiallySent`] error.
+    ///
+    /// [`PartiallySent`]: ExpoNotificationError::PartiallySent
+    pub result: Result<Vec<PushTicket>, ExpoNotificationError>,
+}
+
+impl ChunkResult {
+    /// The recipients of the chunk, each with the position of its message and its ticket, or
+    /// `None` if it was not accepted.
+    fn recipient_tickets(&self) -> impl Iterator<Item = (usize, &PushToken, Option<&PushTicket>)> {
+        let tickets: Box<dyn Iterator<Item = Option<&PushTicket>>> = match &self.result {
+            Ok(tickets) => Box::new(tickets.iter().map(Some)),
+            Err(ExpoNotificationError::PartiallySent { tickets, .. }) => {
+                Box::new(tickets.iter().map(Option::as_ref))
+            }
+            Err(_) => Box::new(std::iter::repeat(None)),
+        };
+        self.recipients
+            .iter()
+            .zip(tickets)
+            .map(|((index, token), ticket)| (*index, token, ticket))
+    }
+}
+
+/// The outcome of a [`send_push_notifications_batch`], reported chunk by chunk so that a
+/// failing chunk does not hide the tickets of the chunks that were delivered.
+///
+/// [`send_push_notifications_batch`]: crate::ExpoNotificationsClient::send_push_notifications_batch
+///
+/// ## Example:
+///
+/// ```
+/// # use expo_server_sdk::{ExpoNotificationsClient, message::*};
+/// # use std::str::FromStr;
+/// # tokio_test::block_on(async {
+/// let token = PushToken::from_str("ExpoPushToken[my-token]").unwrap();
+/// let msgs = vec![PushMessage::new(token).body("test notification")];
+///
+/// let client = ExpoNotificationsClient::new();
+/// let result = client.send_push_notifications_batch(&msgs).await;
+///
+/// for (index, token, ticket) in result.tickets() {
+///     println!("message {} to {:?} gave {:?}", index, token, ticket);
+/// }
+/// let to_retry: Vec<_> = result.failed_messages().map(|index| &msgs[index]).collect();
+/// # });
+/// ```
+#[derive(Debug, Default)]
+pub struct PushBatchResult {
+    pub chunks: Vec<ChunkResult>,
+}
+
+impl PushBatchResult {
+    /// Whether every chunk was accepted by the push notification server.
+    pub fn is_complete(&self) -> bool {
+        self.chunks.iter().all(|chunk| chunk.result.is_ok())
+    }
+
+    /// The tickets received, along with the position of the message and the push token
+    /// they belong to.
+    pub fn tickets(&self) -> impl Iterator<Item = (usize, &PushToken, &PushTicket)> {
+        self.chunks.iter().flat_map(|chunk| {
+            chunk
+                .recipient_tickets()
+                .filter_map(|(index, token, ticket)| Some((index, token, ticket?)))
+        })
+    }
+
+    /// The tickets that are errors, along with the position of the message and the push token
+    /// they belong to. With a `resend_policy`, these are the notifications that ultimately
+    /// failed.
+    pub fn failed_tickets(&self) -> impl Iterator<Item = (usize, &PushToken, &PushTicket)> {
+        self.tickets()
+            .filter(|(_, _, ticket)| matches!(ticket, PushTicket::Error { .. }))
+    }
+
+    /// The chunks that failed.
+    pub fn failed_chunks(&self) -> impl Iterator<Item = &ChunkResult> {
+        self.chunks.iter().filter(|chunk| chunk.result.is_err())
+    }
+
+    /// The recipients that were not sent to because their chunk failed, along with the
+    /// position of their message.
+    pub fn failed_recipients(&self) -> impl Iterator<Item = (usize, &PushToken)> {
+        self.failed_chunks().flat_map(|chunk| {
+            chunk
+                .recipient_tickets()
+                .filter(|(_, _, ticket)| ticket.is_none())
+                .map(|(index, token, _)| (index, token))
+        })
+    }
+
+    /// The positions of the messages with recipients that were not sent to because their
+    /// chunk failed. Some other recipients of such a message may have been sent to, see
+    /// `failed_recipients`.
+    pub fn failed_messages(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut last = None;
+        self.failed_recipients()
+            .map(|(index, _)| index)
+            .filter(move |index| last.replace(*index) != Some(*index))
+    }
+
+    /// All the tickets in recipient order, or the error of the first failed chunk.
+    pub fn into_result(self) -> Result<Vec<PushTicket>, ExpoNotificationError> {
+        let mut tickets = Vec::new();
+        for chunk in self.chunks {
+            tickets.extend(chunk.result?);
+        }
+        Ok(tickets)
+    }
+}
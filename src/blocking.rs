@@ -0,0 +1,150 @@
+//! A synchronous client, for programs that do not run an async runtime.
+//!
+//! The blocking [`ExpoNotificationsClient`] drives an async
+//! [`crate::ExpoNotificationsClient`] on its own single-threaded runtime, so requests are
+//! built, chunked, compressed and retried exactly the same way.
+//!
+//! It must not be used from within an async runtime: calling its methods there panics.
+//!
+//! ## Example:
+//!
+//! ```
+//! # use expo_server_sdk::{blocking::ExpoNotificationsClient, message::*, GzipPolicy};
+//! # use std::str::FromStr;
+//! let token = PushToken::from_str("ExpoPushToken[my-token]").unwrap();
+//! let msg = PushMessage::new(token).body("test notification");
+//!
+//! let client: ExpoNotificationsClient = expo_server_sdk::ExpoNotificationsClient::new()
+//!     .gzip(GzipPolicy::Always)
+//!     .into();
+//! let result = client.send_push_notification(&msg);
+//! ```
+
+use std::{borrow::Borrow, collections::HashMap};
+
+use tokio::runtime::{Builder, Runtime};
+
+use crate::{
+    batch::PushBatchResult,
+    error::ExpoNotificationError,
+    message::{PushMessage, PushToken},
+    response::{PushReceipt, PushReceiptId, PushTicket},
+};
+
+/// The blocking counterpart of [`crate::ExpoNotificationsClient`].
+pub struct ExpoNotificationsClient {
+    inner: crate::ExpoNotificationsClient,
+    runtime: Runtime,
+}
+
+impl ExpoNotificationsClient {
+    /// Create a new client with the default configuration. Configure an async
+    /// [`crate::ExpoNotificationsClient`] and convert it with `into` to change it.
+    pub fn new() -> Self {
+        crate::ExpoNotificationsClient::new().into()
+    }
+
+    /// The async client doing the work.
+    pub fn inner(&self) -> &crate::ExpoNotificationsClient {
+        &self.inner
+    }
+
+    /// Sends a single [`PushMessage`] to the push notification server.
+    pub fn send_push_notification(
+        &self,
+        message: &PushMessage,
+    ) -> Result<PushTicket, ExpoNotificationError> {
+        self.runtime
+            .block_on(self.inner.send_push_notification(message))
+    }
+
+    /// Sends an iterator of [`PushMessage`] to the server.
+    /// See [`crate::ExpoNotificationsClient::send_push_notifications`].
+    pub fn send_push_notifications(
+        &self,
+        messages: impl IntoIterator<Item = impl Borrow<PushMessage>>,
+    ) -> Result<Vec<PushTicket>, ExpoNotificationError> {
+        self.runtime
+            .block_on(self.inner.send_push_notifications(messages))
+    }
+
+    /// Sends an iterator of [`PushMessage`] to the server and reports the outcome of every chunk.
+    /// See [`crate::ExpoNotificationsClient::send_push_notifications_batch`].
+    pub fn send_push_notifications_batch(
+        &self,
+        messages: impl IntoIterator<Item = impl Borrow<PushMessage>>,
+    ) -> PushBatchResult {
+        self.runtime
+            .block_on(self.inner.send_push_notifications_batch(messages))
+    }
+
+    /// Sends [`PushMessage`]s tagged with a key of your choosing.
+    /// See [`crate::ExpoNotificationsClient::send_push_notifications_keyed`].
+    pub fn send_push_notifications_keyed<K: Clone>(
+        &self,
+        messages: impl IntoIterator<Item = (K, impl Borrow<PushMessage>)>,
+    ) -> Result<Vec<(K, PushToken, PushTicket)>, ExpoNotificationError> {
+        self.runtime
+            .block_on(self.inner.send_push_notifications_keyed(messages))
+    }
+
+    /// Send a single chunk of [`PushMessage`] to the server.
+    /// See [`crate::ExpoNotificationsClient::send_push_notifications_in_one_chunk`].
+    pub fn send_push_notifications_in_one_chunk(
+        &self,
+        messages: impl IntoIterator<Item = impl Borrow<PushMessage>>,
+    ) -> Result<Vec<PushTicket>, ExpoNotificationError> {
+        self.runtime
+            .block_on(self.inner.send_push_notifications_in_one_chunk(messages))
+    }
+
+    /// Get a push notification receipt.
+    pub fn get_push_receipt(
+        &self,
+        receipt_id: &PushReceiptId,
+    ) -> Result<Option<PushReceipt>, ExpoNotificationError> {
+        self.runtime
+            .block_on(self.inner.get_push_receipt(receipt_id))
+    }
+
+    /// Get many push notification receipts.
+    pub fn get_push_receipts(
+        &self,
+        receipt_ids: impl IntoIterator<Item = impl Borrow<PushReceiptId>>,
+    ) -> Result<HashMap<PushReceiptId, PushReceipt>, ExpoNotificationError> {
+        self.runtime
+            .block_on(self.inner.get_push_receipts(receipt_ids))
+    }
+
+    /// Get many push notification receipts, each tagged with a key of your choosing.
+    /// See [`crate::ExpoNotificationsClient::get_push_receipts_keyed`].
+    pub fn get_push_receipts_keyed<K>(
+        &self,
+        receipt_ids: impl IntoIterator<Item = (K, impl Borrow<PushReceiptId>)>,
+    ) -> Result<Vec<(K, Option<PushReceipt>)>, ExpoNotificationError> {
+        self.runtime
+            .block_on(self.inner.get_push_receipts_keyed(receipt_ids))
+    }
+
+    /// Get push notification receipts in one request. Avoid sending more than 300 receipt ids.
+    pub fn get_push_receipts_in_one_chunk(
+        &self,
+        receipt_ids: impl IntoIterator<Item = impl Borrow<PushReceiptId>>,
+    ) -> Result<HashMap<PushReceiptId, PushReceipt>, ExpoNotificationError> {
+        self.runtime
+            .block_on(self.inner.get_push_receipts_in_one_chunk(receipt_ids))
+    }
+}
+
+impl Default for ExpoNotificationsClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<crate::ExpoNotificationsClient> for ExpoNotificationsClient {
+    fn from(inner: crate::ExpoNotificationsClient) -> Self {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        ExpoNotificationsClient { inner, runtime }
+    }
+}
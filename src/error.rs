@@ -1,11 +1,70 @@
+use reqwest::StatusCode;
+
+use crate::{
+    message::MessageValidationError,
+    response::{PushTicket, RequestError},
+};
+
 #[derive(Debug, thiserror::Error)]
 pub enum ExpoNotificationError {
     #[error("network request error: {0}")]
     Request(reqwest::Error),
+    /// A custom [`Transport`](crate::transport::Transport) could not send the request.
+    /// `transient` tells whether it never reached the server, so that it is safe to send
+    /// it again.
+    #[error("transport error: {source}")]
+    Transport {
+        source: Box<dyn std::error::Error + Send + Sync>,
+        transient: bool,
+    },
+    /// The push notification server rejected the whole request.
+    /// `errors` is empty if the response body did not describe the errors.
+    #[error("request rejected with status {status}{}", display_request_errors(.errors))]
+    Api {
+        status: StatusCode,
+        errors: Vec<RequestError>,
+    },
     #[error("IO error: {0}")]
     Io(std::io::Error),
+    #[error("invalid response body: {0}")]
+    Deserialize(serde_json::Error),
+    #[error("cannot serialize the request: {0}")]
+    Serialize(serde_json::Error),
+    #[error("the authorization token is not a valid header value")]
+    InvalidAuthorization,
     #[error("nothing to send")]
     Empty,
+    /// `send_push_notification` was given a message without exactly one recipient.
+    #[error("expected a message with one recipient, got {count}")]
+    NotOneRecipient { count: usize },
+    /// The chunk was regrouped by project and only some of the groups were accepted.
+    /// `tickets` holds, in recipient order, the tickets of the accepted groups and `None`
+    /// for the recipients of the refused ones. `error` is why the first group was refused.
+    #[error("chunk partially sent: {error}")]
+    PartiallySent {
+        tickets: Vec<Option<PushTicket>>,
+        error: Box<ExpoNotificationError>,
+    },
+    /// A message failed validation, so its chunk was not sent. `index` is the position of
+    /// the message in the messages given to the client.
+    #[error("message {index} is invalid{}", display_validation_errors(.errors))]
+    InvalidMessage {
+        index: usize,
+        errors: Vec<MessageValidationError>,
+    },
+}
+
+impl ExpoNotificationError {
+    /// Whether the request failed for a reason that may go away by itself, so that it is
+    /// worth sending it again. Unless the request is `idempotent`, only failures where it
+    /// never reached the server qualify: a timed out push may still have been accepted.
+    pub(crate) fn is_retryable(&self, idempotent: bool) -> bool {
+        match self {
+            ExpoNotificationError::Request(e) => e.is_connect() || (idempotent && e.is_timeout()),
+            ExpoNotificationError::Transport { transient, .. } => *transient,
+            _ => false,
+        }
+    }
 }
 
 impl From<reqwest::Error> for ExpoNotificationError {
@@ -18,3 +77,16 @@ impl From<std::io::Error> for ExpoNotificationError {
         Self::Io(value)
     }
 }
+impl From<serde_json::Error> for ExpoNotificationError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Deserialize(value)
+    }
+}
+
+fn display_request_errors(errors: &[RequestError]) -> String {
+    errors.iter().map(|e| format!(", {e}")).collect()
+}
+
+fn display_validation_errors(errors: &[MessageValidationError]) -> String {
+    errors.iter().map(|e| format!(", {e}")).collect()
+}
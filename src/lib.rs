@@ -24,22 +24,48 @@
 //! # })
 //! ```
 
+pub mod batch;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod error;
 mod gzip_policy;
 pub mod message;
+mod rate_limiter;
+mod receipt_poller;
 pub mod response;
+mod retry_policy;
+mod token_store;
+pub mod transport;
 pub use gzip_policy::GzipPolicy;
+pub use receipt_poller::{PolledReceipt, ReceiptPoller};
+pub use retry_policy::RetryPolicy;
 use serde::Serialize;
+pub use token_store::{InMemoryTokenStore, TokenStore};
 
-use std::{borrow::Borrow, collections::HashMap};
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    sync::{Arc, OnceLock},
+    time::{Duration, SystemTime},
+};
 
+use batch::{ChunkResult, PushBatchResult};
 use error::ExpoNotificationError;
-use message::PushMessage;
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+use message::{PushMessage, PushToken};
+use rate_limiter::RateLimiter;
 use reqwest::{
-    header::{HeaderValue, ACCEPT, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE},
-    Url,
+    header::{
+        HeaderMap, HeaderValue, ACCEPT, ACCEPT_ENCODING, AUTHORIZATION, CONTENT_ENCODING,
+        CONTENT_TYPE, RETRY_AFTER,
+    },
+    StatusCode, Url,
+};
+use response::{
+    ErrorResponse, PushReceipt, PushReceiptId, PushResponse, PushTicket, ReceiptResponse,
+    RequestError, RequestErrorCode,
 };
-use response::{PushReceipt, PushReceiptId, PushResponse, PushTicket, ReceiptResponse};
+use transport::{ReqwestTransport, Transport, TransportRequest, TransportResponse};
 
 /// The `PushNotifier` takes one or more `PushMessage` to send to the push notification server
 ///
@@ -62,9 +88,18 @@ pub struct ExpoNotificationsClient {
     pub receipt_url: Url,
     pub authorization: Option<String>,
     pub gzip: GzipPolicy,
+    pub retry_policy: RetryPolicy,
     pub push_chunk_size: usize,
     pub receipt_chunk_size: usize,
-    client: reqwest::Client,
+    pub max_in_flight_chunks: usize,
+    pub timeout: Option<Duration>,
+    pub validate_messages: bool,
+    pub resend_policy: Option<RetryPolicy>,
+    connect_timeout: Option<Duration>,
+    rate_limiter: Option<RateLimiter>,
+    token_store: Option<Arc<dyn TokenStore>>,
+    transport: Option<Arc<dyn Transport>>,
+    default_transport: OnceLock<ReqwestTransport>,
 }
 
 impl ExpoNotificationsClient {
@@ -77,9 +112,18 @@ impl ExpoNotificationsClient {
                 .unwrap(),
             authorization: None,
             gzip: Default::default(),
+            retry_policy: Default::default(),
             push_chunk_size: 100,
             receipt_chunk_size: 300,
-            client: reqwest::Client::builder().gzip(true).build().unwrap(),
+            max_in_flight_chunks: 1,
+            timeout: None,
+            validate_messages: false,
+            resend_policy: None,
+            connect_timeout: None,
+            rate_limiter: None,
+            token_store: None,
+            transport: None,
+            default_transport: OnceLock::new(),
         }
     }
 
@@ -97,6 +141,59 @@ impl ExpoNotificationsClient {
         self
     }
 
+    /// Specify the [`Transport`] the requests are sent through.
+    /// Default is a [`ReqwestTransport`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a `connect_timeout` was set, as it only applies to the default transport.
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        assert!(
+            self.connect_timeout.is_none(),
+            "connect_timeout only applies to the default transport, set it on the transport instead"
+        );
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Specify the [`reqwest::Client`] the requests are sent with, to configure proxies, TLS,
+    /// connection pooling... Response decompression needs the client to have `gzip` enabled,
+    /// which is the default.
+    ///
+    /// This replaces the transport, including one set with `transport`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a `connect_timeout` was set. Set the connect timeout on the client instead.
+    pub fn http_client(self, client: reqwest::Client) -> Self {
+        self.transport(ReqwestTransport::new(client))
+    }
+
+    /// Specify the time limit for each request, from connecting until the response body has
+    /// been read. Default is no time limit.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Specify the time limit for connecting to the push notification server.
+    /// Default is no time limit.
+    ///
+    /// It only applies to the default transport. When using your own client, set the connect
+    /// timeout on it before passing it to `http_client`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a transport was set with `transport` or `http_client`.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        assert!(
+            self.transport.is_none(),
+            "connect_timeout only applies to the default transport, set it on the transport instead"
+        );
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
     /// Specify the authorization token (if enhanced push security is enabled).
     pub fn authorization(mut self, token: Option<String>) -> Self {
         self.authorization = token;
@@ -109,6 +206,30 @@ impl ExpoNotificationsClient {
         self
     }
 
+    /// Specify how requests failing with a transient error are retried.
+    /// Use [`RetryPolicy::never`] to disable retrying.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Limit the number of notifications sent per second. Chunks of messages are held back
+    /// until they fit under the limit. Expo allows about 600 notifications per second per project.
+    /// Default is no limit.
+    pub fn rate_limit(mut self, notifications_per_second: Option<u32>) -> Self {
+        self.rate_limiter = notifications_per_second
+            .filter(|&rate| rate > 0)
+            .map(RateLimiter::new);
+        self
+    }
+
+    /// Specify the [`TokenStore`] to remove the push tokens Expo reports as no longer
+    /// registered from. Default is none.
+    pub fn token_store(mut self, token_store: impl TokenStore + 'static) -> Self {
+        self.token_store = Some(Arc::new(token_store));
+        self
+    }
+
     // Specify the chunk size to use for `send_push_notifications`. Should not be greater than 100 (the default).
     pub fn push_chunk_size(mut self, chunk_size: usize) -> Self {
         self.push_chunk_size = chunk_size;
@@ -121,49 +242,372 @@ impl ExpoNotificationsClient {
         self
     }
 
+    /// Specify how many chunks `send_push_notifications` and `get_push_receipts` may have in
+    /// flight at the same time. Results are still returned in the order of the input.
+    /// Default is 1, sending the chunks one after another.
+    pub fn max_in_flight_chunks(mut self, max_in_flight_chunks: usize) -> Self {
+        self.max_in_flight_chunks = max_in_flight_chunks;
+        self
+    }
+
+    /// Specify whether messages are checked with [`PushMessage::validate`] before they are
+    /// sent. A chunk with an invalid message fails with
+    /// [`ExpoNotificationError::InvalidMessage`] without being sent.
+    /// Default is false, leaving the checks to the push notification server.
+    pub fn validate_messages(mut self, validate_messages: bool) -> Self {
+        self.validate_messages = validate_messages;
+        self
+    }
+
+    /// Specify whether, and under which policy, the recipients whose ticket is an error worth
+    /// retrying (see [`Outcome::RetryLater`]) are sent again, while the rest of their chunk
+    /// was accepted. Only `max_attempts` and the delays of the policy are used. The tickets
+    /// returned are the last ones received, so those still in error ultimately failed. If a
+    /// resend fails, the chunk fails with a
+    /// [`PartiallySent`](ExpoNotificationError::PartiallySent) error, with no ticket for the
+    /// recipients that were being sent again.
+    /// Default is none, returning the first tickets as they are.
+    ///
+    /// [`Outcome::RetryLater`]: response::Outcome::RetryLater
+    pub fn resend_policy(mut self, resend_policy: Option<RetryPolicy>) -> Self {
+        self.resend_policy = resend_policy;
+        self
+    }
+
     /// Sends a single [`PushMessage`] to the push notification server.
+    ///
+    /// The message must have exactly one recipient. Use `send_push_notifications` to get the
+    /// ticket of each recipient of a message with several.
     pub async fn send_push_notification(
         &self,
         message: &PushMessage,
     ) -> Result<PushTicket, ExpoNotificationError> {
-        let mut result = self
+        if message.to.len() != 1 {
+            return Err(ExpoNotificationError::NotOneRecipient {
+                count: message.to.len(),
+            });
+        }
+        let result = self
             .send_push_notifications_in_one_chunk(std::iter::once(message))
             .await?;
-        Ok(result.pop().unwrap())
+        Ok(result.into_iter().next().unwrap())
     }
 
     /// Sends an iterator of [`PushMessage`] to the server.
-    /// This method automatically chunks the input message iterator.
+    /// This method automatically chunks the input message iterator, and sends up to
+    /// `max_in_flight_chunks` chunks concurrently.
+    ///
+    /// Stops at the first chunk that fails. Use `send_push_notifications_batch` to learn which
+    /// messages were delivered in that case.
     pub async fn send_push_notifications(
         &self,
         messages: impl IntoIterator<Item = impl Borrow<PushMessage>>,
     ) -> Result<Vec<PushTicket>, ExpoNotificationError> {
-        let mut messages = messages.into_iter().peekable();
-        let mut receipts = Vec::with_capacity(messages.size_hint().1.unwrap_or(0));
-        while messages.peek().is_some() {
-            let chunk_receipts = self
-                .send_push_notifications_in_one_chunk(messages.by_ref().take(self.push_chunk_size))
-                .await?;
-            receipts.extend(chunk_receipts.into_iter());
+        let messages = messages.into_iter();
+        let tickets = Vec::with_capacity(messages.size_hint().1.unwrap_or(0));
+        self.send_push_notifications_stream(stream::iter(messages))
+            .map(|chunk| chunk.result)
+            .try_fold(tickets, |mut tickets, chunk_tickets| async move {
+                tickets.extend(chunk_tickets);
+                Ok(tickets)
+            })
+            .await
+    }
+
+    /// Sends an iterator of [`PushMessage`] to the server, like `send_push_notifications`,
+    /// but keeps going when a chunk fails and reports the outcome of every chunk.
+    pub async fn send_push_notifications_batch(
+        &self,
+        messages: impl IntoIterator<Item = impl Borrow<PushMessage>>,
+    ) -> PushBatchResult {
+        PushBatchResult {
+            chunks: self
+                .send_push_notifications_stream(stream::iter(messages))
+                .collect()
+                .await,
         }
-        Ok(receipts)
+    }
+
+    /// Sends a [`Stream`] of [`PushMessage`] to the server, chunk by chunk, and returns a stream
+    /// of the outcome of each chunk, in order. Use `futures::stream::iter` to send an iterator.
+    ///
+    /// Messages are only pulled from the input, and chunks only sent, as the returned stream is
+    /// polled, with at most `max_in_flight_chunks` chunks in flight. A chunk is sent once it
+    /// holds `push_chunk_size` recipients, once the next message would not fit, or once the
+    /// input ends. Only a message with more than `push_chunk_size` recipients is split across
+    /// chunks.
+    ///
+    /// ## Example:
+    ///
+    /// ```
+    /// # use expo_server_sdk::{ExpoNotificationsClient, message::*};
+    /// # use futures_util::{stream, StreamExt};
+    /// # use std::str::FromStr;
+    /// # tokio_test::block_on(async {
+    /// let token = PushToken::from_str("ExpoPushToken[my-token]").unwrap();
+    /// let msgs = (0..1000).map(|i| PushMessage::new(token.clone()).body(i.to_string()));
+    ///
+    /// let client = ExpoNotificationsClient::new();
+    /// let mut results = client.send_push_notifications_stream(stream::iter(msgs));
+    /// while let Some(chunk) = results.next().await {
+    ///     // persist the tickets of `chunk.messages` before the next chunk is sent
+    /// #   break;
+    /// }
+    /// # });
+    /// ```
+    pub fn send_push_notifications_stream<'a>(
+        &'a self,
+        messages: impl Stream<Item = impl Borrow<PushMessage> + 'a> + 'a,
+    ) -> impl Stream<Item = ChunkResult> + 'a {
+        chunk_by_recipients(messages, self.push_chunk_size)
+            .map(move |chunk| {
+                let positions = chunk[0].0..chunk[chunk.len() - 1].0 + 1;
+                let recipients = chunk
+                    .iter()
+                    .flat_map(|(i, message)| {
+                        let message: &PushMessage = message.borrow();
+                        message.to.iter().map(move |token| (*i, token.clone()))
+                    })
+                    .collect();
+                let (indices, chunk): (Vec<_>, Vec<_>) = chunk.into_iter().unzip();
+                async move {
+                    let result = self
+                        .send_push_notifications_in_one_chunk(chunk)
+                        .await
+                        .map_err(|e| match e {
+                            // Report the position in the input rather than in the chunk.
+                            ExpoNotificationError::InvalidMessage { index, errors } => {
+                                ExpoNotificationError::InvalidMessage {
+                                    index: indices[index],
+                                    errors,
+                                }
+                            }
+                            e => e,
+                        });
+                    ChunkResult {
+                        messages: positions,
+                        recipients,
+                        result,
+                    }
+                }
+            })
+            .buffered(self.max_in_flight_chunks.max(1))
+    }
+
+    /// Sends [`PushMessage`]s tagged with a key of your choosing (a user id, a row id...),
+    /// like `send_push_notifications`, and returns every ticket along with the key of the
+    /// message and the push token it belongs to.
+    ///
+    /// ## Example:
+    ///
+    /// ```
+    /// # use expo_server_sdk::{ExpoNotificationsClient, message::*};
+    /// # use std::str::FromStr;
+    /// # tokio_test::block_on(async {
+    /// let token = PushToken::from_str("ExpoPushToken[my-token]").unwrap();
+    /// let msg = PushMessage::new(token).body("test notification");
+    /// let user_id = 42;
+    ///
+    /// let client = ExpoNotificationsClient::new();
+    /// if let Ok(tickets) = client.send_push_notifications_keyed([(user_id, msg)]).await {
+    ///     let ids = tickets
+    ///         .iter()
+    ///         .filter_map(|(user_id, _, ticket)| Some((*user_id, ticket.receipt_id()?)));
+    ///     let receipts = client.get_push_receipts_keyed(ids).await;
+    /// }
+    /// # });
+    /// ```
+    pub async fn send_push_notifications_keyed<K: Clone>(
+        &self,
+        messages: impl IntoIterator<Item = (K, impl Borrow<PushMessage>)>,
+    ) -> Result<Vec<(K, PushToken, PushTicket)>, ExpoNotificationError> {
+        let (keys, messages): (Vec<_>, Vec<_>) = messages.into_iter().unzip();
+        let recipients = keys
+            .into_iter()
+            .zip(&messages)
+            .flat_map(|(key, message)| {
+                let tokens = message.borrow().to.iter();
+                tokens.map(move |token| (key.clone(), token.clone()))
+            })
+            .collect::<Vec<_>>();
+        let tickets = self.send_push_notifications(messages).await?;
+        Ok(recipients
+            .into_iter()
+            .zip(tickets)
+            .map(|((key, token), ticket)| (key, token, ticket))
+            .collect())
     }
 
     /// Send a single chunk of [`PushMessage`] to the server.
     ///
-    /// If the provided messages chunk contains more than 100 items this might fail.
+    /// If the provided messages chunk has more than 100 recipients this might fail.
     /// Prefer the `send_push_notifications` in such situation.
+    ///
+    /// If the server refuses the chunk because it mixes push tokens of several Expo projects,
+    /// the messages are regrouped by project and each group is sent on its own. If only some
+    /// of the groups are accepted, their tickets are returned in a
+    /// [`PartiallySent`](ExpoNotificationError::PartiallySent) error.
+    ///
+    /// With a `resend_policy`, the recipients whose ticket is a retryable error are sent again.
     pub async fn send_push_notifications_in_one_chunk(
         &self,
         messages: impl IntoIterator<Item = impl Borrow<PushMessage>>,
+    ) -> Result<Vec<PushTicket>, ExpoNotificationError> {
+        let messages = messages.into_iter().collect::<Vec<_>>();
+        if self.validate_messages {
+            for (index, message) in messages.iter().enumerate() {
+                message
+                    .borrow()
+                    .validate()
+                    .map_err(|errors| ExpoNotificationError::InvalidMessage { index, errors })?;
+            }
+        }
+        let (mut tickets, mut error) = match self.send_regrouped(&messages).await {
+            Ok(tickets) => (tickets.into_iter().map(Some).collect(), None),
+            Err(ExpoNotificationError::PartiallySent { tickets, error }) => (tickets, Some(*error)),
+            Err(error) => return Err(error),
+        };
+        if let Some(resend_policy) = self.resend_policy {
+            if let Err(e) = self
+                .resend_retryable(&messages, &mut tickets, resend_policy)
+                .await
+            {
+                error.get_or_insert(e);
+            }
+        }
+        self.remove_unregistered_tokens(
+            tickets
+                .iter()
+                .flatten()
+                .filter_map(PushTicket::unregistered_token),
+        )
+        .await;
+        partial_result(tickets, error)
+    }
+
+    /// Send the messages in one request, or, if the server refuses it because it mixes push
+    /// tokens of several Expo projects, in one request per project.
+    async fn send_regrouped(
+        &self,
+        messages: &[impl Borrow<PushMessage>],
+    ) -> Result<Vec<PushTicket>, ExpoNotificationError> {
+        match self.send_chunk(messages).await {
+            Err(ExpoNotificationError::Api { status, errors }) => {
+                match group_by_project(messages, &errors) {
+                    Some(groups) => self.send_groups(messages, groups).await,
+                    None => Err(ExpoNotificationError::Api { status, errors }),
+                }
+            }
+            res => res,
+        }
+    }
+
+    async fn send_chunk(
+        &self,
+        messages: &[impl Borrow<PushMessage>],
     ) -> Result<Vec<PushTicket>, ExpoNotificationError> {
         let mut buffer = Vec::new();
-        serialize_into_json_list(messages.into_iter(), &mut buffer)?;
-        let res = self.send_request(self.push_url.clone(), buffer).await?;
-        let res = res.json::<PushResponse>().await?;
+        let messages = messages.iter().map(|message| message.borrow());
+        serialize_into_json_list::<PushMessage>(messages.clone(), &mut buffer)?;
+        if let Some(rate_limiter) = self.rate_limiter.as_ref() {
+            rate_limiter
+                .acquire(messages.map(|message| message.to.len()).sum())
+                .await;
+        }
+        let res = self
+            .send_request(self.push_url.clone(), buffer, false)
+            .await?;
+        let res = serde_json::from_slice::<PushResponse>(&res.body)?;
         Ok(res.data)
     }
 
+    /// Send each group of recipients in its own request and put the tickets back in the order
+    /// of the recipients of `messages`. If some groups are refused, the others are still sent
+    /// and their tickets returned in a `PartiallySent` error.
+    async fn send_groups(
+        &self,
+        messages: &[impl Borrow<PushMessage>],
+        groups: Vec<Vec<Recipient>>,
+    ) -> Result<Vec<PushTicket>, ExpoNotificationError> {
+        let mut offsets = Vec::with_capacity(messages.len());
+        let mut recipient_count = 0;
+        for message in messages {
+            offsets.push(recipient_count);
+            recipient_count += message.borrow().to.len();
+        }
+        let mut tickets = std::iter::repeat_with(|| None)
+            .take(recipient_count)
+            .collect::<Vec<_>>();
+        let mut first_error = None;
+        for group in groups {
+            match self
+                .send_chunk(&messages_for_recipients(messages, &group))
+                .await
+            {
+                Ok(group_tickets) => {
+                    for ((i, token), ticket) in group.into_iter().zip(group_tickets) {
+                        tickets[offsets[i] + token] = Some(ticket);
+                    }
+                }
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        partial_result(tickets, first_error)
+    }
+
+    /// Send again, waiting as `resend_policy` says between attempts, the recipients whose
+    /// ticket is a retryable error, and replace their tickets with the new ones.
+    /// If a resend fails, the recipients it did not reach are left without a ticket.
+    async fn resend_retryable(
+        &self,
+        messages: &[impl Borrow<PushMessage>],
+        tickets: &mut [Option<PushTicket>],
+        resend_policy: RetryPolicy,
+    ) -> Result<(), ExpoNotificationError> {
+        let recipients = messages
+            .iter()
+            .enumerate()
+            .flat_map(|(i, message)| (0..message.borrow().to.len()).map(move |j| (i, j)))
+            .collect::<Vec<Recipient>>();
+        for attempt in 1..resend_policy.max_attempts {
+            let (positions, resent): (Vec<usize>, Vec<Recipient>) = tickets
+                .iter()
+                .zip(&recipients)
+                .enumerate()
+                .filter(|(_, (ticket, _))| {
+                    ticket
+                        .as_ref()
+                        .is_some_and(|ticket| ticket.outcome().is_retryable())
+                })
+                .map(|(position, (_, &recipient))| (position, recipient))
+                .unzip();
+            if positions.is_empty() {
+                break;
+            }
+
+            tokio::time::sleep(resend_policy.delay(attempt, None)).await;
+            let (new_tickets, error) = match self
+                .send_regrouped(&messages_for_recipients(messages, &resent))
+                .await
+            {
+                Ok(new_tickets) => (new_tickets.into_iter().map(Some).collect(), None),
+                Err(ExpoNotificationError::PartiallySent { tickets, error }) => {
+                    (tickets, Some(*error))
+                }
+                Err(error) => (vec![None; positions.len()], Some(error)),
+            };
+            for (position, ticket) in positions.into_iter().zip(new_tickets) {
+                tickets[position] = ticket;
+            }
+            if let Some(error) = error {
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
     /// Get a push notification receipt.
     pub async fn get_push_receipt(
         &self,
@@ -176,19 +620,65 @@ impl ExpoNotificationsClient {
     }
 
     /// Get many push notification receipts.
+    /// Like `send_push_notifications`, the ids are chunked and up to `max_in_flight_chunks`
+    /// chunks are requested concurrently.
     pub async fn get_push_receipts(
         &self,
         receipt_ids: impl IntoIterator<Item = impl Borrow<PushReceiptId>>,
     ) -> Result<HashMap<PushReceiptId, PushReceipt>, ExpoNotificationError> {
-        let mut ids = receipt_ids.into_iter().peekable();
-        let mut out = HashMap::new();
-        while ids.peek().is_some() {
-            let chunk_receipts = self
-                .get_push_receipts_in_one_chunk(ids.by_ref().take(self.receipt_chunk_size))
-                .await?;
-            out.extend(chunk_receipts.into_iter());
-        }
-        Ok(out)
+        self.get_push_receipts_by_chunk(receipt_ids)
+            .map(|(_, result)| result)
+            .try_fold(HashMap::new(), |mut out, chunk_receipts| async move {
+                out.extend(chunk_receipts);
+                Ok(out)
+            })
+            .await
+    }
+
+    /// Request the receipts chunk by chunk, up to `max_in_flight_chunks` at a time, and yield
+    /// the ids of each chunk along with its outcome, in the order the requests complete.
+    pub(crate) fn get_push_receipts_by_chunk(
+        &self,
+        receipt_ids: impl IntoIterator<Item = impl Borrow<PushReceiptId>>,
+    ) -> impl Stream<
+        Item = (
+            Vec<PushReceiptId>,
+            Result<HashMap<PushReceiptId, PushReceipt>, ExpoNotificationError>,
+        ),
+    > + '_ {
+        // Owned chunks, so that the futures do not borrow from the input and stay `Send`
+        // whatever its lifetime.
+        let chunks = chunks(receipt_ids.into_iter(), self.receipt_chunk_size)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .map(|id| id.borrow().clone())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        stream::iter(chunks)
+            .map(move |ids| async move {
+                let result = self.get_push_receipts_in_one_chunk(&ids).await;
+                (ids, result)
+            })
+            .buffer_unordered(self.max_in_flight_chunks.max(1))
+    }
+
+    /// Get many push notification receipts, each tagged with a key of your choosing, and
+    /// return every receipt along with its key. The receipt is `None` if it is not
+    /// available (yet).
+    pub async fn get_push_receipts_keyed<K>(
+        &self,
+        receipt_ids: impl IntoIterator<Item = (K, impl Borrow<PushReceiptId>)>,
+    ) -> Result<Vec<(K, Option<PushReceipt>)>, ExpoNotificationError> {
+        let receipt_ids = receipt_ids.into_iter().collect::<Vec<_>>();
+        let mut receipts = self
+            .get_push_receipts(receipt_ids.iter().map(|(_, id)| id.borrow()))
+            .await?;
+        Ok(receipt_ids
+            .into_iter()
+            .map(|(key, id)| (key, receipts.remove(id.borrow())))
+            .collect())
     }
 
     /// Get push notification receipts in one request. Avoid sending more than 300 receipt ids.
@@ -198,64 +688,322 @@ impl ExpoNotificationsClient {
     ) -> Result<HashMap<PushReceiptId, PushReceipt>, ExpoNotificationError> {
         let mut buffer: Vec<u8> = "{\"ids\":".as_bytes().into();
         serialize_into_json_list(receipt_ids.into_iter(), &mut buffer)?;
-        buffer.push('}' as u8);
-        let res = self.send_request(self.receipt_url.clone(), buffer).await?;
-        let res = res.json::<ReceiptResponse>().await?;
+        buffer.push(b'}');
+        let res = self
+            .send_request(self.receipt_url.clone(), buffer, true)
+            .await?;
+        let res = serde_json::from_slice::<ReceiptResponse>(&res.body)?;
+        self.remove_unregistered_tokens(
+            res.data
+                .values()
+                .filter_map(PushReceipt::unregistered_token),
+        )
+        .await;
         Ok(res.data)
     }
 
+    async fn remove_unregistered_tokens(&self, tokens: impl Iterator<Item = &PushToken>) {
+        if let Some(token_store) = self.token_store.as_ref() {
+            for token in tokens {
+                token_store.remove_token(token).await;
+            }
+        }
+    }
+
+    /// The transport set with `transport` or `http_client`, or else the default
+    /// [`ReqwestTransport`], built on first use with the `connect_timeout`.
+    fn active_transport(&self) -> &dyn Transport {
+        match &self.transport {
+            Some(transport) => transport.as_ref(),
+            None => self.default_transport.get_or_init(|| {
+                let mut builder = reqwest::Client::builder().gzip(true);
+                if let Some(connect_timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(connect_timeout);
+                }
+                ReqwestTransport::new(builder.build().unwrap())
+            }),
+        }
+    }
+
+    /// Send the request, retrying it under the retry policy. Pushes are not `idempotent`:
+    /// sending one again could deliver its notifications twice, so only the failures where
+    /// Expo did not accept it are retried.
     async fn send_request(
         &self,
         url: Url,
         buffer: Vec<u8>,
-    ) -> Result<reqwest::Response, ExpoNotificationError> {
-        let mut req = self
-            .client
-            .post(url)
-            .header(ACCEPT, HeaderValue::from_static("application/json"))
-            .header(ACCEPT_ENCODING, HeaderValue::from_static("gzip"))
-            .header(ACCEPT_ENCODING, HeaderValue::from_static("deflate"))
-            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-
-        if let Some(auth_token) = self.authorization.as_ref() {
-            req = req.bearer_auth(auth_token);
-        }
-
+        idempotent: bool,
+    ) -> Result<TransportResponse, ExpoNotificationError> {
         let should_compress = match self.gzip {
             GzipPolicy::ZipGreaterThanTreshold(treshold) if buffer.len() > treshold => true,
             GzipPolicy::Always => true,
             _ => false,
         };
 
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+        headers.append(ACCEPT_ENCODING, HeaderValue::from_static("gzip"));
+        headers.append(ACCEPT_ENCODING, HeaderValue::from_static("deflate"));
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        if let Some(auth_token) = self.authorization.as_ref() {
+            let mut value = HeaderValue::try_from(format!("Bearer {auth_token}"))
+                .map_err(|_| ExpoNotificationError::InvalidAuthorization)?;
+            value.set_sensitive(true);
+            headers.insert(AUTHORIZATION, value);
+        }
+
         let body = if should_compress {
             use flate2::write::GzEncoder;
             use flate2::Compression;
             use std::io::Write;
 
-            req = req.header(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+            headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
             let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
-            encoder.write(&buffer)?;
+            encoder.write_all(&buffer)?;
             encoder.finish()?
         } else {
             buffer
         };
 
-        req = req.body(body);
-        Ok(req.send().await?.error_for_status()?)
+        let request = TransportRequest {
+            url,
+            headers,
+            body: body.into(),
+            timeout: self.timeout,
+        };
+
+        let mut attempt = 1;
+        loop {
+            let res = self.active_transport().send(request.clone()).await;
+
+            // `Some(retry_after)` if the failure is transient and the request may be retried.
+            let retry = match &res {
+                Ok(res) if is_retryable_status(res.status, idempotent) => {
+                    Some(retry_after(&res.headers))
+                }
+                Err(e) if e.is_retryable(idempotent) => Some(None),
+                _ => None,
+            };
+
+            match retry {
+                Some(retry_after) if attempt < self.retry_policy.max_attempts => {
+                    tokio::time::sleep(self.retry_policy.delay(attempt, retry_after)).await;
+                    attempt += 1;
+                }
+                _ => {
+                    let res = res?;
+                    if res.status.is_client_error() || res.status.is_server_error() {
+                        let errors = serde_json::from_slice::<ErrorResponse>(&res.body)
+                            .map(|res| res.errors)
+                            .unwrap_or_default();
+                        return Err(ExpoNotificationError::Api {
+                            status: res.status,
+                            errors,
+                        });
+                    }
+                    return Ok(res);
+                }
+            }
+        }
+    }
+}
+
+impl Default for ExpoNotificationsClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `429 Too Many Requests` and `503 Service Unavailable` mean the request was refused. Other
+/// `5xx` statuses may come after the server handled the request, for example from a gateway
+/// timing out.
+fn is_retryable_status(status: StatusCode, idempotent: bool) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::SERVICE_UNAVAILABLE
+        || (idempotent && status.is_server_error())
+}
+
+/// Parse the `Retry-After` header, given either in seconds or as an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// A recipient of a chunk: the position of its message and of its token in the `to` of
+/// the message.
+type Recipient = (usize, usize);
+
+/// Copy the messages of the recipients, each copy sent only to its recipients in `messages`.
+/// Recipients of the same message that follow each other stay together in one copy.
+fn messages_for_recipients(
+    messages: &[impl Borrow<PushMessage>],
+    recipients: &[Recipient],
+) -> Vec<PushMessage> {
+    let mut copies: Vec<(usize, PushMessage)> = Vec::new();
+    for &(i, token) in recipients {
+        let token = messages[i].borrow().to[token].clone();
+        match copies.last_mut() {
+            Some((last, message)) if *last == i => message.to.push(token),
+            _ => {
+                let mut message = messages[i].borrow().clone();
+                message.to = vec![token];
+                copies.push((i, message));
+            }
+        }
+    }
+    copies.into_iter().map(|(_, message)| message).collect()
+}
+
+/// If the request was refused because the messages are for several Expo projects, group the
+/// recipients by project, using the tokens listed in the error details.
+/// Recipients whose token is not listed are put in a group of their own.
+fn group_by_project(
+    messages: &[impl Borrow<PushMessage>],
+    errors: &[RequestError],
+) -> Option<Vec<Vec<Recipient>>> {
+    let details = errors
+        .iter()
+        .find(|e| e.code == RequestErrorCode::PushTooManyExperienceIds)?
+        .details
+        .as_ref()?
+        .as_object()?;
+    let projects = details
+        .iter()
+        .flat_map(|(project, tokens)| {
+            let tokens = tokens.as_array().into_iter().flatten();
+            tokens.filter_map(move |token| Some((token.as_str()?, project.as_str())))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut groups: Vec<(Option<&str>, Vec<Recipient>)> = Vec::new();
+    for (i, message) in messages.iter().enumerate() {
+        for (j, token) in message.borrow().to.iter().enumerate() {
+            let project = projects.get(token.as_str()).copied();
+            match groups.iter_mut().find(|(p, _)| *p == project) {
+                Some((_, group)) => group.push((i, j)),
+                None => groups.push((project, vec![(i, j)])),
+            }
+        }
+    }
+    // Resending as is would fail the same way.
+    (groups.len() > 1).then(|| groups.into_iter().map(|(_, group)| group).collect())
+}
+
+/// Split the iterator into chunks of at most `size` items.
+fn chunks<I: Iterator>(mut iter: I, size: usize) -> impl Iterator<Item = Vec<I::Item>> {
+    let size = size.max(1);
+    std::iter::from_fn(move || {
+        let chunk = iter.by_ref().take(size).collect::<Vec<_>>();
+        (!chunk.is_empty()).then_some(chunk)
+    })
+}
+
+/// A message of a chunk, or a copy of it for some of its recipients.
+enum MessagePart<M> {
+    Whole(M),
+    Part(Box<PushMessage>),
+}
+
+impl<M: Borrow<PushMessage>> Borrow<PushMessage> for MessagePart<M> {
+    fn borrow(&self) -> &PushMessage {
+        match self {
+            MessagePart::Whole(message) => message.borrow(),
+            MessagePart::Part(message) => message,
+        }
     }
 }
 
+/// The tickets of all the recipients if there was no error, or else the error, along with the
+/// tickets received if there are any.
+fn partial_result(
+    tickets: Vec<Option<PushTicket>>,
+    error: Option<ExpoNotificationError>,
+) -> Result<Vec<PushTicket>, ExpoNotificationError> {
+    match error {
+        None => Ok(tickets.into_iter().flatten().collect()),
+        Some(error) if tickets.iter().all(Option::is_none) => Err(error),
+        Some(error) => Err(ExpoNotificationError::PartiallySent {
+            tickets,
+            error: Box::new(error),
+        }),
+    }
+}
+
+/// Split the stream of messages into chunks of at most `size` recipients, each message along
+/// with its position in the stream. A message with more recipients than that is split into
+/// parts of `size` recipients, each filling a chunk, and a last part.
+fn chunk_by_recipients<'a, M: Borrow<PushMessage> + 'a>(
+    messages: impl Stream<Item = M> + 'a,
+    size: usize,
+) -> impl Stream<Item = Vec<(usize, MessagePart<M>)>> + 'a {
+    let size = size.max(1);
+    let messages = messages.enumerate().flat_map(move |(i, message)| {
+        let parts = if message.borrow().to.len() <= size {
+            vec![(i, MessagePart::Whole(message))]
+        } else {
+            let message = message.borrow();
+            message
+                .to
+                .chunks(size)
+                .map(|tokens| {
+                    let mut part = message.clone();
+                    part.to = tokens.to_vec();
+                    (i, MessagePart::Part(Box::new(part)))
+                })
+                .collect()
+        };
+        stream::iter(parts)
+    });
+    let state = (Box::pin(messages.fuse()), None);
+    Box::pin(stream::unfold(
+        state,
+        move |(mut messages, mut next)| async move {
+            let mut chunk = Vec::new();
+            let mut count = 0;
+            while count < size {
+                let message = match next.take() {
+                    Some(message) => message,
+                    None => match messages.next().await {
+                        Some(message) => message,
+                        None => break,
+                    },
+                };
+                let part: &PushMessage = message.1.borrow();
+                let recipients = part.to.len();
+                if !chunk.is_empty() && count + recipients > size {
+                    next = Some(message);
+                    break;
+                }
+                count += recipients;
+                chunk.push(message);
+            }
+            (!chunk.is_empty()).then_some((chunk, (messages, next)))
+        },
+    ))
+}
+
+/// Serialize the items as a JSON list into the buffer.
 fn serialize_into_json_list<T: Serialize>(
     mut data: impl Iterator<Item = impl Borrow<T>>,
     mut buffer: &mut Vec<u8>,
 ) -> Result<(), ExpoNotificationError> {
-    buffer.push('[' as u8);
+    buffer.push(b'[');
     let first_msg = data.next().ok_or(ExpoNotificationError::Empty)?;
-    serde_json::to_writer(&mut buffer, first_msg.borrow()).unwrap();
-    data.for_each(|msg| {
-        buffer.push(',' as u8);
-        serde_json::to_writer(&mut buffer, msg.borrow()).unwrap();
-    });
-    buffer.push(']' as u8);
+    serde_json::to_writer(&mut buffer, first_msg.borrow())
+        .map_err(ExpoNotificationError::Serialize)?;
+    for msg in data {
+        buffer.push(b',');
+        serde_json::to_writer(&mut buffer, msg.borrow())
+            .map_err(ExpoNotificationError::Serialize)?;
+    }
+    buffer.push(b']');
     Ok(())
 }
@@ -1,9 +1,12 @@
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
-use std::str::FromStr;
+use std::{
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 /// A PushToken must be of the format `ExpoPushToken[xxx]` or `ExponentPushToken[xxx]`.
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, PartialEq, Eq, Hash)]
 pub struct PushToken(String);
 
 impl<'de> Deserialize<'de> for PushToken {
@@ -16,6 +19,12 @@ impl<'de> Deserialize<'de> for PushToken {
     }
 }
 
+impl PushToken {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 #[error("expect format `ExpoPushToken[xxx]` or `ExponentPushToken[xxx]` but given {0}")]
 pub struct PushTokenParseError(String);
@@ -60,8 +69,9 @@ impl FromStr for PushToken {
 /// See the Expo documentation on [message format] for more details.
 ///
 /// [message format]: https://docs.expo.io/versions/latest/guides/push-notifications#message-format
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
 pub enum Priority {
+    #[default]
     #[serde(rename = "default")]
     Default,
     #[serde(rename = "normal")]
@@ -70,12 +80,6 @@ pub enum Priority {
     High,
 }
 
-impl Default for Priority {
-    fn default() -> Self {
-        Priority::Default
-    }
-}
-
 impl FromStr for Priority {
     type Err = serde_json::Error;
     fn from_str(s: &str) -> Result<Priority, Self::Err> {
@@ -86,25 +90,229 @@ impl FromStr for Priority {
 /// A sound to play when the recipient receives this notification. Specify
 /// "default" to play the device's default notification sound, or omit this
 /// field to play no sound.
-#[derive(Debug, Deserialize, Serialize, Clone)]
+///
+/// ## Example:
+///
+/// ```
+/// # use expo_server_sdk::message::*;
+/// # use std::str::FromStr;
+/// let sound = Sound::from_str("notification.wav").unwrap();
+/// let alarm = Sound::Critical(CriticalSound::new().name("alarm.wav").volume(0.8).unwrap());
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+#[serde(from = "RawSound", into = "RawSound")]
 pub enum Sound {
-    #[serde(rename = "default")]
+    #[default]
     Default,
+
+    /// The file name of a sound bundled with the app.
+    Custom(String),
+
+    /// iOS only. The object form of a sound, needed to play critical alerts.
+    Critical(CriticalSound),
 }
 
-impl Default for Sound {
-    fn default() -> Self {
-        Sound::Default
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum RawSound {
+    Name(String),
+    Object(CriticalSound),
+}
+
+impl From<RawSound> for Sound {
+    fn from(raw: RawSound) -> Self {
+        match raw {
+            RawSound::Name(name) if name == "default" => Sound::Default,
+            RawSound::Name(name) => Sound::Custom(name),
+            RawSound::Object(critical) => Sound::Critical(critical),
+        }
+    }
+}
+
+impl From<Sound> for RawSound {
+    fn from(sound: Sound) -> Self {
+        match sound {
+            Sound::Default => RawSound::Name("default".to_owned()),
+            Sound::Custom(name) => RawSound::Name(name),
+            Sound::Critical(critical) => RawSound::Object(critical),
+        }
     }
 }
 
+/// Parses `default`, the file name of a custom sound, or a JSON string or object
+/// like `{"critical": true, "volume": 0.5}`.
 impl FromStr for Sound {
     type Err = serde_json::Error;
     fn from_str(s: &str) -> Result<Sound, Self::Err> {
-        serde_json::from_str(s)
+        if s.starts_with('{') {
+            return serde_json::from_str(s).map(Sound::Critical);
+        }
+        if s.starts_with('"') {
+            return serde_json::from_str(s);
+        }
+        Ok(match s {
+            "default" => Sound::Default,
+            name => Sound::Custom(name.to_owned()),
+        })
+    }
+}
+
+/// The object form of a [`Sound`]. Critical alerts play even when the device is muted or
+/// in Do Not Disturb mode, and require a special entitlement from Apple.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(try_from = "RawCriticalSound")]
+pub struct CriticalSound {
+    critical: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    volume: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct RawCriticalSound {
+    #[serde(default)]
+    critical: bool,
+    name: Option<String>,
+    volume: Option<f64>,
+}
+
+impl TryFrom<RawCriticalSound> for CriticalSound {
+    type Error = SoundVolumeError;
+
+    fn try_from(raw: RawCriticalSound) -> Result<Self, Self::Error> {
+        let sound = CriticalSound::new().critical(raw.critical);
+        let sound = match raw.name {
+            Some(name) => sound.name(name),
+            None => sound,
+        };
+        match raw.volume {
+            Some(volume) => sound.volume(volume),
+            None => Ok(sound),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("expect a sound volume between 0 and 1 but given {0}")]
+pub struct SoundVolumeError(f64);
+
+impl CriticalSound {
+    /// Create a critical alert playing the default sound at the system volume.
+    pub fn new() -> CriticalSound {
+        CriticalSound {
+            critical: true,
+            name: None,
+            volume: None,
+        }
+    }
+
+    pub fn critical(mut self, critical: bool) -> Self {
+        self.critical = critical;
+        self
+    }
+
+    /// The file name of a sound bundled with the app, or "default".
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// The volume of a critical alert, between 0 (silent) and 1 (full volume).
+    pub fn volume(mut self, volume: f64) -> Result<Self, SoundVolumeError> {
+        if !(0.0..=1.0).contains(&volume) {
+            return Err(SoundVolumeError(volume));
+        }
+        self.volume = Some(volume);
+        Ok(self)
+    }
+
+    pub fn is_critical(&self) -> bool {
+        self.critical
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn get_volume(&self) -> Option<f64> {
+        self.volume
+    }
+}
+
+impl Default for CriticalSound {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
+/// The importance and delivery timing of a notification on iOS 15+.
+///
+/// See the Apple documentation on [interruption levels] for more details.
+///
+/// [interruption levels]: https://developer.apple.com/documentation/usernotifications/unnotificationinterruptionlevel
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub enum InterruptionLevel {
+    #[serde(rename = "active")]
+    Active,
+    #[serde(rename = "critical")]
+    Critical,
+    #[serde(rename = "passive")]
+    Passive,
+    #[serde(rename = "time-sensitive")]
+    TimeSensitive,
+}
+
+/// Content displayed along with the notification, when the app supports it.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct RichContent {
+    /// The URL of an image shown in the notification.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PushDataError {
+    #[error("cannot serialize data: {0}")]
+    Serialize(#[from] serde_json::Error),
+
+    #[error("expect data to serialize to a JSON object but given {0}")]
+    NotAnObject(Value),
+}
+
+/// The largest payload Expo accepts for a message, in bytes.
+pub const MAX_PAYLOAD_SIZE: usize = 4096;
+
+/// The largest badge count, as APNs reads it as a signed 32-bit integer.
+pub const MAX_BADGE: u32 = i32::MAX as u32;
+
+/// A reason for Expo to reject a [`PushMessage`], found by [`PushMessage::validate`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MessageValidationError {
+    #[error("the message has no recipient")]
+    NoRecipient,
+
+    #[error("the payload is {size} bytes, over the {MAX_PAYLOAD_SIZE} bytes limit")]
+    PayloadTooBig { size: usize },
+
+    #[error("the message has no title, body nor data")]
+    NoContent,
+
+    #[error("data must be a JSON object")]
+    DataNotAnObject,
+
+    #[error("the expiration is before the Unix epoch")]
+    ExpirationBeforeEpoch,
+
+    #[error("ttl and expiration are both set, expiration would override ttl")]
+    TtlAndExpiration,
+
+    #[error("badge {0} is over the maximum of {MAX_BADGE}")]
+    BadgeOutOfRange(u32),
+}
+
 /// A `PushMessage` struct modelled after the one listed [here]:
 ///
 /// [here]: https://docs.expo.io/versions/latest/guides/push-notifications#message-format
@@ -118,9 +326,16 @@ impl FromStr for Sound {
 /// let token = PushToken::from_str("ExpoPushToken[my-token]").unwrap();
 /// let mut msg = PushMessage::new(token).body("test notification");
 /// ```
-#[derive(Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct PushMessage {
-    pub to: PushToken,
+    /// The recipients of the message. Each of them counts as one notification against the
+    /// 100 notifications per request limit, and gets its own ticket.
+    #[serde(
+        serialize_with = "serialize_recipients",
+        deserialize_with = "deserialize_recipients"
+    )]
+    pub to: Vec<PushToken>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<Value>,
@@ -128,50 +343,198 @@ pub struct PushMessage {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
 
+    /// iOS only. Shown below the title.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtitle: Option<String>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub body: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sound: Option<Sound>,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub ttl: Option<u32>,
+    /// How long the notification may be kept for redelivery if the device is offline.
+    /// Sent in whole seconds, rounded up.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_ttl",
+        deserialize_with = "deserialize_ttl"
+    )]
+    pub ttl: Option<Duration>,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub expiration: Option<u32>,
+    /// Until when the notification may be kept for redelivery. Overrides `ttl`.
+    /// Sent as a Unix timestamp in whole seconds, rounded up.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_expiration",
+        deserialize_with = "deserialize_expiration"
+    )]
+    pub expiration: Option<SystemTime>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub priority: Option<Priority>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub badge: Option<u32>,
+
+    /// Android only. The notification channel the notification is displayed in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<String>,
+
+    /// The notification category, defining the actions shown with the notification.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category_id: Option<String>,
+
+    /// iOS only. Lets a notification service extension modify the notification.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mutable_content: Option<bool>,
+
+    /// iOS only. Wakes the app in the background to handle the notification.
+    #[serde(rename = "_contentAvailable", skip_serializing_if = "Option::is_none")]
+    pub content_available: Option<bool>,
+
+    /// iOS only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interruption_level: Option<InterruptionLevel>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rich_content: Option<RichContent>,
 }
 
 impl PushMessage {
     pub fn new(push_token: PushToken) -> PushMessage {
+        PushMessage::with_recipients([push_token])
+    }
+
+    /// Create a message sending the same content to several push tokens.
+    pub fn with_recipients(push_tokens: impl IntoIterator<Item = PushToken>) -> PushMessage {
         PushMessage {
-            to: push_token,
+            to: push_tokens.into_iter().collect(),
             data: None,
             title: None,
+            subtitle: None,
             body: None,
             sound: None,
             ttl: None,
             expiration: None,
             priority: None,
             badge: None,
+            channel_id: None,
+            category_id: None,
+            mutable_content: None,
+            content_available: None,
+            interruption_level: None,
+            rich_content: None,
         }
     }
 
+    /// Add a push token to the recipients of the message.
+    pub fn recipient(mut self, push_token: PushToken) -> Self {
+        self.to.push(push_token);
+        self
+    }
+
     pub fn data(mut self, data: Value) -> Self {
         self.data = Some(data);
         self
     }
 
+    /// Set the data from any serializable value, which must serialize to a JSON object
+    /// as Expo rejects other payloads.
+    ///
+    /// ## Example:
+    ///
+    /// ```
+    /// # use expo_server_sdk::message::*;
+    /// # use std::str::FromStr;
+    /// #[derive(serde::Serialize, serde::Deserialize)]
+    /// struct Chat {
+    ///     room: u64,
+    /// }
+    ///
+    /// let token = PushToken::from_str("ExpoPushToken[my-token]").unwrap();
+    /// let msg = PushMessage::new(token).try_data(&Chat { room: 42 }).unwrap();
+    /// let chat: Chat = msg.parse_data().unwrap().unwrap();
+    /// ```
+    pub fn try_data(mut self, data: &impl Serialize) -> Result<Self, PushDataError> {
+        match serde_json::to_value(data)? {
+            data @ Value::Object(_) => {
+                self.data = Some(data);
+                Ok(self)
+            }
+            data => Err(PushDataError::NotAnObject(data)),
+        }
+    }
+
+    /// Check the message for the mistakes Expo would reject it for, and return all of them.
+    ///
+    /// The payload size is that of the message serialized to JSON, leaving the recipients
+    /// out. A message without title nor body is accepted if it has data or is a background
+    /// notification (`content_available`).
+    pub fn validate(&self) -> Result<(), Vec<MessageValidationError>> {
+        let mut errors = Vec::new();
+        if self.to.is_empty() {
+            errors.push(MessageValidationError::NoRecipient);
+        }
+
+        if self
+            .expiration
+            .is_some_and(|expiration| expiration < UNIX_EPOCH)
+        {
+            errors.push(MessageValidationError::ExpirationBeforeEpoch);
+        }
+
+        // Only the expiration can make serialization fail, and it is reported above.
+        if let Ok(mut payload) = serde_json::to_value(self) {
+            payload.as_object_mut().unwrap().remove("to");
+            let size = serde_json::to_vec(&payload).unwrap().len();
+            if size > MAX_PAYLOAD_SIZE {
+                errors.push(MessageValidationError::PayloadTooBig { size });
+            }
+        }
+
+        let is_set = |text: &Option<String>| text.as_deref().is_some_and(|t| !t.is_empty());
+        if !is_set(&self.title)
+            && !is_set(&self.body)
+            && self.data.is_none()
+            && self.content_available != Some(true)
+        {
+            errors.push(MessageValidationError::NoContent);
+        }
+        if self.data.as_ref().is_some_and(|data| !data.is_object()) {
+            errors.push(MessageValidationError::DataNotAnObject);
+        }
+        if self.ttl.is_some() && self.expiration.is_some() {
+            errors.push(MessageValidationError::TtlAndExpiration);
+        }
+        if let Some(badge) = self.badge.filter(|&badge| badge > MAX_BADGE) {
+            errors.push(MessageValidationError::BadgeOutOfRange(badge));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Deserialize the data of the message, if any, into `T`.
+    pub fn parse_data<T: DeserializeOwned>(&self) -> Result<Option<T>, serde_json::Error> {
+        self.data.clone().map(serde_json::from_value).transpose()
+    }
+
     pub fn title(mut self, title: impl Into<String>) -> Self {
         self.title = Some(title.into());
         self
     }
 
+    pub fn subtitle(mut self, subtitle: impl Into<String>) -> Self {
+        self.subtitle = Some(subtitle.into());
+        self
+    }
+
     pub fn body(mut self, body: impl Into<String>) -> Self {
         self.body = Some(body.into());
         self
@@ -182,16 +545,38 @@ impl PushMessage {
         self
     }
 
-    pub fn ttl(mut self, ttl: u32) -> Self {
-        self.ttl = Some(ttl);
+    /// Set the ttl in seconds. See `ttl_duration`.
+    pub fn ttl(self, ttl: u32) -> Self {
+        self.ttl_duration(Duration::from_secs(ttl.into()))
+    }
+
+    /// Set the ttl. A fraction of a second is rounded up, so that a short ttl does not become
+    /// zero.
+    pub fn ttl_duration(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(round_up_to_secs(ttl));
         self
     }
 
-    pub fn expiration(mut self, expiration: u32) -> Self {
-        self.expiration = Some(expiration);
+    /// Set the expiration as a Unix timestamp in seconds. See `expires_at`.
+    pub fn expiration(self, expiration: u32) -> Self {
+        self.expires_at(UNIX_EPOCH + Duration::from_secs(expiration.into()))
+    }
+
+    /// Set the expiration. A fraction of a second is rounded up.
+    pub fn expires_at(mut self, expiration: SystemTime) -> Self {
+        self.expiration = Some(match expiration.duration_since(UNIX_EPOCH) {
+            Ok(since_epoch) => UNIX_EPOCH + round_up_to_secs(since_epoch),
+            // Reported by `validate` and when serializing.
+            Err(_) => expiration,
+        });
         self
     }
 
+    /// Set the expiration to `duration` from now, rounded up to a whole second.
+    pub fn expires_in(self, duration: Duration) -> Self {
+        self.expires_at(SystemTime::now() + duration)
+    }
+
     pub fn priority(mut self, priority: Priority) -> Self {
         self.priority = Some(priority);
         self
@@ -201,4 +586,106 @@ impl PushMessage {
         self.badge = Some(badge);
         self
     }
+
+    pub fn channel_id(mut self, channel_id: impl Into<String>) -> Self {
+        self.channel_id = Some(channel_id.into());
+        self
+    }
+
+    pub fn category_id(mut self, category_id: impl Into<String>) -> Self {
+        self.category_id = Some(category_id.into());
+        self
+    }
+
+    pub fn mutable_content(mut self, mutable_content: bool) -> Self {
+        self.mutable_content = Some(mutable_content);
+        self
+    }
+
+    pub fn content_available(mut self, content_available: bool) -> Self {
+        self.content_available = Some(content_available);
+        self
+    }
+
+    pub fn interruption_level(mut self, interruption_level: InterruptionLevel) -> Self {
+        self.interruption_level = Some(interruption_level);
+        self
+    }
+
+    pub fn rich_content(mut self, rich_content: RichContent) -> Self {
+        self.rich_content = Some(rich_content);
+        self
+    }
+
+    /// Show the image at `url` in the notification.
+    pub fn image(mut self, url: impl Into<String>) -> Self {
+        self.rich_content.get_or_insert_with(Default::default).image = Some(url.into());
+        self
+    }
+}
+
+/// The push notification server only takes whole seconds.
+fn round_up_to_secs(duration: Duration) -> Duration {
+    let secs = duration.as_secs();
+    Duration::from_secs(if duration.subsec_nanos() > 0 {
+        secs.saturating_add(1)
+    } else {
+        secs
+    })
+}
+
+fn serialize_ttl<S: Serializer>(ttl: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+    ttl.map(|ttl| round_up_to_secs(ttl).as_secs())
+        .serialize(serializer)
+}
+
+fn serialize_expiration<S: Serializer>(
+    expiration: &Option<SystemTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let expiration = expiration
+        .map(|expiration| expiration.duration_since(UNIX_EPOCH))
+        .transpose()
+        .map_err(|_| serde::ser::Error::custom(MessageValidationError::ExpirationBeforeEpoch))?;
+    expiration
+        .map(|expiration| round_up_to_secs(expiration).as_secs())
+        .serialize(serializer)
+}
+
+fn deserialize_ttl<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    let ttl = Option::<u64>::deserialize(deserializer)?;
+    Ok(ttl.map(Duration::from_secs))
+}
+
+fn deserialize_expiration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<SystemTime>, D::Error> {
+    let expiration = Option::<u64>::deserialize(deserializer)?;
+    Ok(expiration.map(|expiration| UNIX_EPOCH + Duration::from_secs(expiration)))
+}
+
+/// A single recipient is sent as a plain token, like most messages are written.
+fn serialize_recipients<S: Serializer>(to: &[PushToken], serializer: S) -> Result<S::Ok, S::Error> {
+    match to {
+        [push_token] => push_token.serialize(serializer),
+        _ => to.serialize(serializer),
+    }
+}
+
+fn deserialize_recipients<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<PushToken>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Recipients {
+        One(PushToken),
+        Many(Vec<PushToken>),
+    }
+
+    Ok(match Recipients::deserialize(deserializer)? {
+        Recipients::One(push_token) => vec![push_token],
+        Recipients::Many(push_tokens) => push_tokens,
+    })
 }
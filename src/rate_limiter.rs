@@ -0,0 +1,52 @@
+use std::{sync::Mutex, time::Duration};
+
+use tokio::time::Instant;
+
+/// A token bucket pacing the notifications sent to the push notification servers.
+///
+/// The bucket holds at most one second worth of notifications. Sending a chunk takes as many
+/// tokens as it has messages; when there are not enough tokens the bucket goes into debt and
+/// the chunk waits until the debt is paid back, so a chunk bigger than the rate is still sent.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    per_second: f64,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(per_second: u32) -> Self {
+        let per_second = f64::from(per_second);
+        RateLimiter {
+            per_second,
+            state: Mutex::new(State {
+                tokens: per_second,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Wait until `count` notifications may be sent.
+    pub async fn acquire(&self, count: usize) {
+        let wait = {
+            let mut state = self.state.lock().unwrap();
+            let now = Instant::now();
+            let refill = (now - state.updated).as_secs_f64() * self.per_second;
+            state.tokens = (state.tokens + refill).min(self.per_second) - count as f64;
+            state.updated = now;
+            if state.tokens < 0.0 {
+                Duration::from_secs_f64(-state.tokens / self.per_second)
+            } else {
+                Duration::ZERO
+            }
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
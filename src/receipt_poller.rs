@@ -0,0 +1,243 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use futures_util::StreamExt;
+use tokio::sync::mpsc;
+
+use crate::{
+    error::ExpoNotificationError,
+    response::{PushReceipt, PushReceiptId},
+    ExpoNotificationsClient,
+};
+
+/// The final outcome of a receipt checked by a [`ReceiptPoller`].
+#[derive(Debug)]
+pub struct PolledReceipt {
+    pub id: PushReceiptId,
+
+    /// The receipt, or `None` if it could not be fetched within `max_attempts` checks.
+    pub receipt: Option<PushReceipt>,
+
+    /// Why the last check failed, if the request for it did. It is shared by all the
+    /// receipts checked in that request.
+    pub error: Option<Arc<ExpoNotificationError>>,
+}
+
+/// Checks the receipts of sent notifications once they are due.
+///
+/// Expo recommends checking receipts about 15 minutes after sending. The poller waits `delay`
+/// after the send time of each receipt id, fetches the due ids in `receipt_chunk_size`
+/// batches, and checks again after `retry_delay` the ones that are not available yet,
+/// up to `max_attempts` times.
+///
+/// ## Example:
+///
+/// ```no_run
+/// # use expo_server_sdk::{ExpoNotificationsClient, ReceiptPoller, message::*, response::*};
+/// # use std::{str::FromStr, sync::Arc, time::SystemTime};
+/// # use tokio::sync::mpsc;
+/// # tokio_test::block_on(async {
+/// let client = Arc::new(ExpoNotificationsClient::new());
+///
+/// let (ids_tx, ids_rx) = mpsc::channel(100);
+/// let (receipts_tx, mut receipts_rx) = mpsc::channel(100);
+/// tokio::spawn(ReceiptPoller::new(client.clone()).run(ids_rx, receipts_tx));
+///
+/// let token = PushToken::from_str("ExpoPushToken[my-token]").unwrap();
+/// let msg = PushMessage::new(token).body("test notification");
+/// if let Ok(PushTicket::Ok { id }) = client.send_push_notification(&msg).await {
+///     ids_tx.send((id, SystemTime::now())).await.unwrap();
+/// }
+/// drop(ids_tx);
+///
+/// while let Some(polled) = receipts_rx.recv().await {
+///     println!("{:?}: {:?}", polled.id, polled.receipt);
+/// }
+/// # });
+/// ```
+pub struct ReceiptPoller {
+    client: Arc<ExpoNotificationsClient>,
+    pub delay: Duration,
+    pub retry_delay: Duration,
+    pub max_attempts: u32,
+    queue: BinaryHeap<Reverse<Pending>>,
+    next_seq: u64,
+}
+
+struct Pending {
+    due: SystemTime,
+    // Keeps ids due at the same time in the order they were enqueued.
+    seq: u64,
+    id: PushReceiptId,
+    attempts: u32,
+}
+
+impl PartialEq for Pending {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Pending {}
+
+impl PartialOrd for Pending {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Pending {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.due, self.seq).cmp(&(other.due, other.seq))
+    }
+}
+
+impl ReceiptPoller {
+    /// Create a poller checking receipts 15 minutes after sending, then every 5 minutes,
+    /// giving up after 5 checks.
+    pub fn new(client: Arc<ExpoNotificationsClient>) -> Self {
+        ReceiptPoller {
+            client,
+            delay: Duration::from_secs(15 * 60),
+            retry_delay: Duration::from_secs(5 * 60),
+            max_attempts: 5,
+            queue: BinaryHeap::new(),
+            next_seq: 0,
+        }
+    }
+
+    /// Specify how long after sending a receipt is first checked.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Specify how long to wait before checking again a receipt that was not available.
+    pub fn retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Specify how many times a receipt is checked before giving up on it.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Add the receipt id of a notification sent at `sent_at`.
+    pub fn enqueue(&mut self, id: PushReceiptId, sent_at: SystemTime) {
+        self.push(id, sent_at + self.delay, 0);
+    }
+
+    /// The number of receipts waiting to be checked.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// When the next receipt is due to be checked.
+    pub fn next_due(&self) -> Option<SystemTime> {
+        self.queue.peek().map(|Reverse(pending)| pending.due)
+    }
+
+    /// Check the receipts that are due now, and return those that reached a final outcome.
+    pub async fn poll_due(&mut self) -> Vec<PolledReceipt> {
+        let now = SystemTime::now();
+        let mut due = Vec::new();
+        while self.next_due().is_some_and(|next| next <= now) {
+            due.push(self.queue.pop().unwrap().0);
+        }
+        if due.is_empty() {
+            return Vec::new();
+        }
+
+        // A failed request counts as an attempt for every id in it, and only for those.
+        let mut receipts = HashMap::new();
+        let mut errors = HashMap::new();
+        let mut chunks = self
+            .client
+            .get_push_receipts_by_chunk(due.iter().map(|pending| &pending.id));
+        while let Some((ids, result)) = chunks.next().await {
+            match result {
+                Ok(chunk_receipts) => receipts.extend(chunk_receipts),
+                Err(e) => {
+                    let error = Arc::new(e);
+                    errors.extend(ids.into_iter().map(|id| (id, error.clone())));
+                }
+            }
+        }
+        drop(chunks);
+
+        let mut polled = Vec::new();
+        for pending in due {
+            let attempts = pending.attempts + 1;
+            match receipts.remove(&pending.id) {
+                Some(receipt) => polled.push(PolledReceipt {
+                    id: pending.id,
+                    receipt: Some(receipt),
+                    error: None,
+                }),
+                None if attempts >= self.max_attempts => polled.push(PolledReceipt {
+                    error: errors.remove(&pending.id),
+                    id: pending.id,
+                    receipt: None,
+                }),
+                None => self.push(pending.id, now + self.retry_delay, attempts),
+            }
+        }
+        polled
+    }
+
+    /// Check receipts as they become due, taking new receipt ids from `ids` and sending
+    /// the outcomes to `receipts`.
+    ///
+    /// Returns once `ids` is closed and every receipt has been checked, or when `receipts`
+    /// is closed.
+    pub async fn run(
+        mut self,
+        mut ids: mpsc::Receiver<(PushReceiptId, SystemTime)>,
+        receipts: mpsc::Sender<PolledReceipt>,
+    ) {
+        let mut ids_closed = false;
+        loop {
+            let next_due = self.next_due();
+            if ids_closed && next_due.is_none() {
+                return;
+            }
+            let wait = next_due
+                .map(|due| due.duration_since(SystemTime::now()).unwrap_or_default())
+                .unwrap_or(Duration::MAX);
+
+            tokio::select! {
+                id = ids.recv(), if !ids_closed => match id {
+                    Some((id, sent_at)) => self.enqueue(id, sent_at),
+                    None => ids_closed = true,
+                },
+                _ = tokio::time::sleep(wait), if next_due.is_some() => {
+                    for polled in self.poll_due().await {
+                        if receipts.send(polled).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, id: PushReceiptId, due: SystemTime, attempts: u32) {
+        self.queue.push(Reverse(Pending {
+            due,
+            seq: self.next_seq,
+            id,
+            attempts,
+        }));
+        self.next_seq += 1;
+    }
+}
@@ -1,18 +1,52 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    fmt,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
 
 use crate::message::PushToken;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub(crate) struct PushResponse {
     pub data: Vec<PushTicket>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+/// The id Expo gives an accepted notification, to fetch its receipt with.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct PushReceiptId(String);
 
-#[derive(Debug, Deserialize)]
+impl PushReceiptId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for PushReceiptId {
+    fn from(id: String) -> Self {
+        PushReceiptId(id)
+    }
+}
+
+impl FromStr for PushReceiptId {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(PushReceiptId(s.to_owned()))
+    }
+}
+
+impl fmt::Display for PushReceiptId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(tag = "status")]
 pub enum PushTicket {
     #[serde(rename = "ok")]
@@ -20,16 +54,47 @@ pub enum PushTicket {
     #[serde(rename = "error")]
     Error {
         message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
         details: Option<PushReceiptErrorDetails>,
     },
 }
 
-#[derive(Debug, Deserialize)]
+impl PushTicket {
+    /// The id to fetch the receipt of this notification with, if it was accepted.
+    pub fn receipt_id(&self) -> Option<&PushReceiptId> {
+        match self {
+            PushTicket::Ok { id } => Some(id),
+            PushTicket::Error { .. } => None,
+        }
+    }
+
+    /// What to do about the notification. An accepted notification is `Delivered` to Expo;
+    /// check its receipt to know whether it reached the device.
+    pub fn outcome(&self) -> Outcome {
+        match self {
+            PushTicket::Ok { .. } => Outcome::Delivered,
+            PushTicket::Error { details, .. } => Outcome::of_error(details.as_ref()),
+        }
+    }
+
+    /// The push token Expo reported as no longer registered, if any.
+    pub fn unregistered_token(&self) -> Option<&PushToken> {
+        match self {
+            PushTicket::Error {
+                details: Some(details),
+                ..
+            } => details.unregistered_token(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub(crate) struct ReceiptResponse {
     pub data: HashMap<PushReceiptId, PushReceipt>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(tag = "status")]
 pub enum PushReceipt {
     #[serde(rename = "ok")]
@@ -37,20 +102,472 @@ pub enum PushReceipt {
     #[serde(rename = "error")]
     Error {
         message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
         details: Option<PushReceiptErrorDetails>,
     },
 }
 
-#[derive(Debug, Deserialize)]
+impl PushReceipt {
+    /// What to do about the notification.
+    pub fn outcome(&self) -> Outcome {
+        match self {
+            PushReceipt::Ok {} => Outcome::Delivered,
+            PushReceipt::Error { details, .. } => Outcome::of_error(details.as_ref()),
+        }
+    }
+
+    /// The push token Expo reported as no longer registered, if any.
+    pub fn unregistered_token(&self) -> Option<&PushToken> {
+        match self {
+            PushReceipt::Error {
+                details: Some(details),
+                ..
+            } => details.unregistered_token(),
+            _ => None,
+        }
+    }
+}
+
+/// Why a notification could not be delivered, as described [here].
+///
+/// [here]: https://docs.expo.dev/push-notifications/sending-notifications/#individual-errors
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(tag = "error")]
 pub enum PushReceiptErrorDetails {
     DeviceNotRegistered {
         #[serde(rename = "expoPushToken")]
         expo_push_token: PushToken,
+        #[serde(flatten)]
+        context: ErrorContext,
+    },
+    InvalidCredentials {
+        #[serde(flatten)]
+        context: ErrorContext,
+    },
+    MessageTooBig {
+        #[serde(flatten)]
+        context: ErrorContext,
+    },
+    MessageRateExceeded {
+        #[serde(flatten)]
+        context: ErrorContext,
+    },
+    MismatchSenderId {
+        #[serde(flatten)]
+        context: ErrorContext,
+    },
+    ProviderError {
+        #[serde(flatten)]
+        context: ErrorContext,
+    },
+    DeveloperError {
+        #[serde(flatten)]
+        context: ErrorContext,
     },
-    InvalidCredentials {},
-    MessageTooBig {},
-    MessageRateExceeded {},
-    #[serde(other)]
-    UnknownError,
+    ExpoError {
+        #[serde(flatten)]
+        context: ErrorContext,
+    },
+    /// Details this version of the library cannot read, as they were received: an unknown
+    /// code, a code without the fields it comes with, or no code at all.
+    #[serde(untagged)]
+    Unknown(Map<String, Value>),
+}
+
+impl PushReceiptErrorDetails {
+    /// The error code, if there is one.
+    pub fn code(&self) -> Option<PushErrorCode> {
+        match self {
+            PushReceiptErrorDetails::DeviceNotRegistered { .. } => {
+                Some(PushErrorCode::DeviceNotRegistered)
+            }
+            PushReceiptErrorDetails::InvalidCredentials { .. } => {
+                Some(PushErrorCode::InvalidCredentials)
+            }
+            PushReceiptErrorDetails::MessageTooBig { .. } => Some(PushErrorCode::MessageTooBig),
+            PushReceiptErrorDetails::MessageRateExceeded { .. } => {
+                Some(PushErrorCode::MessageRateExceeded)
+            }
+            PushReceiptErrorDetails::MismatchSenderId { .. } => {
+                Some(PushErrorCode::MismatchSenderId)
+            }
+            PushReceiptErrorDetails::ProviderError { .. } => Some(PushErrorCode::ProviderError),
+            PushReceiptErrorDetails::DeveloperError { .. } => Some(PushErrorCode::DeveloperError),
+            PushReceiptErrorDetails::ExpoError { .. } => Some(PushErrorCode::ExpoError),
+            PushReceiptErrorDetails::Unknown(details) => details
+                .get("error")
+                .and_then(Value::as_str)
+                .map(|code| PushErrorCode::from(code.to_owned())),
+        }
+    }
+
+    /// What Expo said about the error besides its code, or `None` for `Unknown` details.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            PushReceiptErrorDetails::DeviceNotRegistered { context, .. }
+            | PushReceiptErrorDetails::InvalidCredentials { context }
+            | PushReceiptErrorDetails::MessageTooBig { context }
+            | PushReceiptErrorDetails::MessageRateExceeded { context }
+            | PushReceiptErrorDetails::MismatchSenderId { context }
+            | PushReceiptErrorDetails::ProviderError { context }
+            | PushReceiptErrorDetails::DeveloperError { context }
+            | PushReceiptErrorDetails::ExpoError { context } => Some(context),
+            PushReceiptErrorDetails::Unknown(_) => None,
+        }
+    }
+
+    /// Who is responsible for the error, if Expo said so.
+    pub fn fault(&self) -> Option<Fault> {
+        match self {
+            PushReceiptErrorDetails::Unknown(details) => details
+                .get("fault")
+                .and_then(Value::as_str)
+                .map(|fault| Fault::from(fault.to_owned())),
+            details => details.context().and_then(|context| context.fault.clone()),
+        }
+    }
+
+    /// What to do about the notification. Errors with an unknown code are classified by
+    /// their `fault`, and like errors without details if it is unknown too.
+    pub fn outcome(&self) -> Outcome {
+        if let Some(outcome) = self.code().as_ref().and_then(PushErrorCode::outcome) {
+            return outcome;
+        }
+        match self.fault() {
+            Some(Fault::Expo | Fault::Provider) => Outcome::RetryLater,
+            Some(Fault::Developer | Fault::Other(_)) | None => Outcome::FixMessage,
+        }
+    }
+
+    fn unregistered_token(&self) -> Option<&PushToken> {
+        match self {
+            PushReceiptErrorDetails::DeviceNotRegistered {
+                expo_push_token, ..
+            } => Some(expo_push_token),
+            _ => None,
+        }
+    }
+}
+
+/// What Expo said about an error besides its code.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorContext {
+    /// Who is responsible for the error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fault: Option<Fault>,
+
+    /// The error Apple Push Notification service gave, if it was involved.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apns: Option<Box<ProviderErrorDetails>>,
+
+    /// The error Firebase Cloud Messaging gave, if it was involved.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fcm: Option<Box<ProviderErrorDetails>>,
+
+    /// When Expo handed the notification to the provider.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_timestamp",
+        deserialize_with = "deserialize_timestamp"
+    )]
+    pub sent_at: Option<SystemTime>,
+
+    /// The fields this version of the library does not know about, as they were received.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum PushErrorCode {
+    /// The device cannot receive push notifications anymore, stop sending to its token.
+    DeviceNotRegistered,
+    /// The push notification credentials of the app are invalid or missing.
+    InvalidCredentials,
+    /// The payload of the message is over 4096 bytes.
+    MessageTooBig,
+    /// Too many messages were sent to the device, slow down.
+    MessageRateExceeded,
+    /// The FCM sender id of the push token does not match the credentials of the app.
+    MismatchSenderId,
+    /// APNs or FCM failed to deliver the notification, see `apns` and `fcm`.
+    ProviderError,
+    /// The message or the setup of the app is wrong.
+    DeveloperError,
+    /// Expo failed to deliver the notification.
+    ExpoError,
+    /// A code this version of the library does not know about.
+    Other(String),
+}
+
+impl PushErrorCode {
+    /// What to do about a notification that failed with this code, or `None` for codes
+    /// this version of the library does not know about.
+    pub fn outcome(&self) -> Option<Outcome> {
+        match self {
+            PushErrorCode::DeviceNotRegistered => Some(Outcome::DropToken),
+            PushErrorCode::InvalidCredentials | PushErrorCode::MismatchSenderId => {
+                Some(Outcome::FixConfig)
+            }
+            PushErrorCode::MessageTooBig | PushErrorCode::DeveloperError => {
+                Some(Outcome::FixMessage)
+            }
+            PushErrorCode::MessageRateExceeded
+            | PushErrorCode::ProviderError
+            | PushErrorCode::ExpoError => Some(Outcome::RetryLater),
+            PushErrorCode::Other(_) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            PushErrorCode::DeviceNotRegistered => "DeviceNotRegistered",
+            PushErrorCode::InvalidCredentials => "InvalidCredentials",
+            PushErrorCode::MessageTooBig => "MessageTooBig",
+            PushErrorCode::MessageRateExceeded => "MessageRateExceeded",
+            PushErrorCode::MismatchSenderId => "MismatchSenderId",
+            PushErrorCode::ProviderError => "ProviderError",
+            PushErrorCode::DeveloperError => "DeveloperError",
+            PushErrorCode::ExpoError => "ExpoError",
+            PushErrorCode::Other(code) => code,
+        }
+    }
+}
+
+impl From<String> for PushErrorCode {
+    fn from(code: String) -> Self {
+        match code.as_str() {
+            "DeviceNotRegistered" => PushErrorCode::DeviceNotRegistered,
+            "InvalidCredentials" => PushErrorCode::InvalidCredentials,
+            "MessageTooBig" => PushErrorCode::MessageTooBig,
+            "MessageRateExceeded" => PushErrorCode::MessageRateExceeded,
+            "MismatchSenderId" => PushErrorCode::MismatchSenderId,
+            "ProviderError" => PushErrorCode::ProviderError,
+            "DeveloperError" => PushErrorCode::DeveloperError,
+            "ExpoError" => PushErrorCode::ExpoError,
+            _ => PushErrorCode::Other(code),
+        }
+    }
+}
+
+impl fmt::Display for PushErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<PushErrorCode> for String {
+    fn from(code: PushErrorCode) -> Self {
+        code.as_str().to_owned()
+    }
+}
+
+/// What to do about a notification, given its ticket or receipt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    /// The notification went through.
+    Delivered,
+    /// The notification failed for a reason that may go away, send it again later.
+    RetryLater,
+    /// The push token is not valid anymore, stop sending to it.
+    DropToken,
+    /// The push notification credentials or setup of the app must be fixed before sending
+    /// again.
+    FixConfig,
+    /// The message would be rejected again as it is.
+    FixMessage,
+}
+
+impl Outcome {
+    /// Errors without details give no reason to expect that sending again would help, so
+    /// they are classified as `FixMessage` rather than resent. So are errors whose details say
+    /// nothing this version of the library understands.
+    fn of_error(details: Option<&PushReceiptErrorDetails>) -> Outcome {
+        details.map_or(Outcome::FixMessage, PushReceiptErrorDetails::outcome)
+    }
+
+    /// Whether sending the notification again as it is may succeed.
+    pub fn is_retryable(&self) -> bool {
+        *self == Outcome::RetryLater
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum Fault {
+    /// The app or the server sending the notifications.
+    Developer,
+    /// The Expo push notification service.
+    Expo,
+    /// APNs or FCM.
+    Provider,
+    /// A value this version of the library does not know about.
+    Other(String),
+}
+
+impl Fault {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Fault::Developer => "developer",
+            Fault::Expo => "expo",
+            Fault::Provider => "provider",
+            Fault::Other(fault) => fault,
+        }
+    }
+}
+
+impl From<String> for Fault {
+    fn from(fault: String) -> Self {
+        match fault.as_str() {
+            "developer" => Fault::Developer,
+            "expo" => Fault::Expo,
+            "provider" => Fault::Provider,
+            _ => Fault::Other(fault),
+        }
+    }
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<Fault> for String {
+    fn from(fault: Fault) -> Self {
+        fault.as_str().to_owned()
+    }
+}
+
+/// The error APNs or FCM gave for a notification.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderErrorDetails {
+    /// The reason the provider gave, like `BadDeviceToken` for APNs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+
+    /// The error code the provider gave, like `UNREGISTERED` for FCM.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// The error code the provider gave, under the name some versions of Expo use.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+
+    /// The HTTP status code the provider answered with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+
+    /// The fields this version of the library does not know about, as they were received.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Write a Unix timestamp in seconds, with a fractional part only if needed.
+fn serialize_timestamp<S: Serializer>(
+    timestamp: &Option<SystemTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let since_epoch = timestamp
+        .map(|timestamp| timestamp.duration_since(UNIX_EPOCH))
+        .transpose()
+        .map_err(serde::ser::Error::custom)?;
+    match since_epoch {
+        Some(since_epoch) if since_epoch.subsec_nanos() == 0 => {
+            serializer.serialize_u64(since_epoch.as_secs())
+        }
+        Some(since_epoch) => serializer.serialize_f64(since_epoch.as_secs_f64()),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Read a Unix timestamp in seconds, possibly with a fractional part.
+fn deserialize_timestamp<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<SystemTime>, D::Error> {
+    let timestamp = Option::<f64>::deserialize(deserializer)?;
+    timestamp
+        .map(|timestamp| {
+            Duration::try_from_secs_f64(timestamp)
+                .map(|since_epoch| UNIX_EPOCH + since_epoch)
+                .map_err(serde::de::Error::custom)
+        })
+        .transpose()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct ErrorResponse {
+    pub errors: Vec<RequestError>,
+}
+
+/// An error the push notification server gave for a whole request, as listed [here].
+///
+/// [here]: https://docs.expo.dev/push-notifications/sending-notifications/#request-errors
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RequestError {
+    pub code: RequestErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum RequestErrorCode {
+    /// The messages are for more than one Expo project. `details` maps each project to
+    /// the push tokens belonging to it.
+    PushTooManyExperienceIds,
+    /// More than 100 messages were sent in one request.
+    PushTooManyNotifications,
+    /// More than 1000 receipts were requested in one request.
+    PushTooManyReceipts,
+    /// The request body is not valid.
+    ValidationError,
+    /// A code this version of the library does not know about.
+    Other(String),
+}
+
+impl RequestErrorCode {
+    pub fn as_str(&self) -> &str {
+        match self {
+            RequestErrorCode::PushTooManyExperienceIds => "PUSH_TOO_MANY_EXPERIENCE_IDS",
+            RequestErrorCode::PushTooManyNotifications => "PUSH_TOO_MANY_NOTIFICATIONS",
+            RequestErrorCode::PushTooManyReceipts => "PUSH_TOO_MANY_RECEIPTS",
+            RequestErrorCode::ValidationError => "VALIDATION_ERROR",
+            RequestErrorCode::Other(code) => code,
+        }
+    }
+}
+
+impl From<String> for RequestErrorCode {
+    fn from(code: String) -> Self {
+        match code.as_str() {
+            "PUSH_TOO_MANY_EXPERIENCE_IDS" => RequestErrorCode::PushTooManyExperienceIds,
+            "PUSH_TOO_MANY_NOTIFICATIONS" => RequestErrorCode::PushTooManyNotifications,
+            "PUSH_TOO_MANY_RECEIPTS" => RequestErrorCode::PushTooManyReceipts,
+            "VALIDATION_ERROR" => RequestErrorCode::ValidationError,
+            _ => RequestErrorCode::Other(code),
+        }
+    }
+}
+
+impl fmt::Display for RequestErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<RequestErrorCode> for String {
+    fn from(code: RequestErrorCode) -> Self {
+        code.as_str().to_owned()
+    }
 }
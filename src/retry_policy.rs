@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+/// The policy under which failed requests to the push notification servers are retried.
+///
+/// A request is retried when the server answers with `429 Too Many Requests` or
+/// `503 Service Unavailable`, or when the connection could not be established. Receipt requests
+/// are also retried on any other `5xx` status or a timeout. Push requests are not, as Expo may
+/// have accepted them already and sending them again would deliver the notifications twice.
+/// Other failures are returned immediately.
+///
+/// The delay before the n-th retry is `base_delay * 2^(n - 1)`, capped at `max_delay`. If the
+/// server sent a `Retry-After` header, the client waits at least that long instead, even past
+/// `max_delay`, as the server would refuse an earlier retry.
+///
+/// ## Example:
+///
+/// ```
+/// # use expo_server_sdk::{ExpoNotificationsClient, RetryPolicy};
+/// # use std::time::Duration;
+/// let client = ExpoNotificationsClient::new().retry_policy(
+///     RetryPolicy::default()
+///         .max_attempts(5)
+///         .base_delay(Duration::from_millis(200)),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one. `1` disables retrying.
+    pub max_attempts: u32,
+
+    /// Delay before the first retry. It is doubled for each following retry.
+    pub base_delay: Duration,
+
+    /// Upper bound for the delay between two attempts.
+    pub max_delay: Duration,
+
+    /// Fraction (between 0 and 1) of each delay that is randomized, so that many clients
+    /// failing at the same time do not retry in lockstep. A `Retry-After` delay is only ever
+    /// lengthened.
+    pub jitter: f64,
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn never() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// The delay to wait after the given (1-based) failed attempt.
+    pub(crate) fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        // `jitter` is a public field, so it may be set out of range without the builder.
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let (delay, factors) = match retry_after {
+            Some(retry_after) => (retry_after, 1.0..=1.0 + jitter),
+            None => (
+                self.base_delay
+                    .saturating_mul(1 << attempt.saturating_sub(1).min(31))
+                    .min(self.max_delay),
+                1.0 - jitter..=1.0,
+            ),
+        };
+
+        if jitter > 0.0 && !delay.is_zero() {
+            delay.mul_f64(rand::thread_rng().gen_range(factors))
+        } else {
+            delay
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, starting with a 500ms delay and never waiting more than 30 seconds.
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.2,
+        }
+    }
+}
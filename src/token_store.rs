@@ -0,0 +1,68 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+
+use crate::message::PushToken;
+
+/// Where the push tokens of your users live, so that the client can forget the ones Expo
+/// reports as no longer registered (`DeviceNotRegistered`).
+///
+/// Once set with [`ExpoNotificationsClient::token_store`], the store is called for every such
+/// token found in the tickets and receipts the client receives, including those fetched by a
+/// [`ReceiptPoller`].
+///
+/// [`ExpoNotificationsClient::token_store`]: crate::ExpoNotificationsClient::token_store
+/// [`ReceiptPoller`]: crate::ReceiptPoller
+#[async_trait]
+pub trait TokenStore: Send + Sync {
+    /// Stop sending notifications to `token`. Errors are up to the store to handle: the
+    /// notification results are returned regardless.
+    async fn remove_token(&self, token: &PushToken);
+}
+
+#[async_trait]
+impl<T: TokenStore + ?Sized> TokenStore for Arc<T> {
+    async fn remove_token(&self, token: &PushToken) {
+        (**self).remove_token(token).await
+    }
+}
+
+/// A [`TokenStore`] keeping the tokens in memory, mostly useful for tests.
+#[derive(Debug, Default)]
+pub struct InMemoryTokenStore {
+    tokens: Mutex<HashSet<PushToken>>,
+}
+
+impl InMemoryTokenStore {
+    pub fn new(tokens: impl IntoIterator<Item = PushToken>) -> Self {
+        InMemoryTokenStore {
+            tokens: Mutex::new(tokens.into_iter().collect()),
+        }
+    }
+
+    pub fn insert(&self, token: PushToken) {
+        self.tokens.lock().unwrap().insert(token);
+    }
+
+    pub fn contains(&self, token: &PushToken) -> bool {
+        self.tokens.lock().unwrap().contains(token)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.lock().unwrap().is_empty()
+    }
+}
+
+#[async_trait]
+impl TokenStore for InMemoryTokenStore {
+    async fn remove_token(&self, token: &PushToken) {
+        self.tokens.lock().unwrap().remove(token);
+    }
+}
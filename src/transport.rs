@@ -0,0 +1,106 @@
+//! The HTTP layer the client sends its requests through.
+//!
+//! [`ExpoNotificationsClient`] builds each request (headers, JSON body, compression) and hands
+//! it to a [`Transport`]. By default this is a [`ReqwestTransport`]; implement the trait to use
+//! another HTTP stack, or enable the `testing` feature to get a [`MockTransport`] recording
+//! the requests and answering with scripted responses.
+//!
+//! [`ExpoNotificationsClient`]: crate::ExpoNotificationsClient
+
+#[cfg(feature = "testing")]
+mod mock;
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use reqwest::{header::HeaderMap, StatusCode, Url};
+
+use crate::error::ExpoNotificationError;
+
+#[cfg(feature = "testing")]
+pub use mock::MockTransport;
+
+/// A `POST` request to one of the push notification server endpoints.
+#[derive(Debug, Clone)]
+pub struct TransportRequest {
+    pub url: Url,
+    pub headers: HeaderMap,
+    /// The JSON body, gzipped if the `content-encoding` header says so.
+    pub body: Bytes,
+    /// The time limit for the whole request, if any.
+    pub timeout: Option<Duration>,
+}
+
+/// The response of the push notification server.
+#[derive(Debug, Clone)]
+pub struct TransportResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// The response body, already decompressed.
+    pub body: Bytes,
+}
+
+/// Sends requests to the push notification servers.
+///
+/// A transport only moves bytes: retrying, error parsing and compression are done by the
+/// client, so a response with an error status must be returned as `Ok`. Report a failure to
+/// get a response as [`ExpoNotificationError::Transport`].
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn send(
+        &self,
+        request: TransportRequest,
+    ) -> Result<TransportResponse, ExpoNotificationError>;
+}
+
+#[async_trait]
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    async fn send(
+        &self,
+        request: TransportRequest,
+    ) -> Result<TransportResponse, ExpoNotificationError> {
+        (**self).send(request).await
+    }
+}
+
+/// The default [`Transport`], sending requests with a [`reqwest::Client`].
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: reqwest::Client) -> Self {
+        ReqwestTransport { client }
+    }
+}
+
+impl Default for ReqwestTransport {
+    fn default() -> Self {
+        Self::new(reqwest::Client::builder().gzip(true).build().unwrap())
+    }
+}
+
+#[async_trait]
+impl Transport for ReqwestTransport {
+    async fn send(
+        &self,
+        request: TransportRequest,
+    ) -> Result<TransportResponse, ExpoNotificationError> {
+        let mut req = self
+            .client
+            .post(request.url)
+            .headers(request.headers)
+            .body(request.body);
+        if let Some(timeout) = request.timeout {
+            req = req.timeout(timeout);
+        }
+        let res = req.send().await?;
+        Ok(TransportResponse {
+            status: res.status(),
+            headers: res.headers().clone(),
+            body: res.bytes().await?,
+        })
+    }
+}
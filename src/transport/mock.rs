@@ -0,0 +1,134 @@
+use std::{collections::VecDeque, io::Read, sync::Mutex};
+
+use async_trait::async_trait;
+use reqwest::{
+    header::{HeaderMap, HeaderValue, CONTENT_ENCODING, CONTENT_TYPE},
+    StatusCode,
+};
+use serde_json::Value;
+
+use super::{Transport, TransportRequest, TransportResponse};
+use crate::error::ExpoNotificationError;
+
+type Handler = dyn Fn(&TransportRequest) -> TransportResponse + Send + Sync;
+
+/// An in-memory [`Transport`] for tests. It records every request and answers with the
+/// scripted responses and errors, in order, then with its handler if it has one.
+///
+/// ## Example:
+///
+/// ```
+/// # use expo_server_sdk::{ExpoNotificationsClient, message::*, transport::*};
+/// # use std::{str::FromStr, sync::Arc};
+/// # use serde_json::json;
+/// # tokio_test::block_on(async {
+/// let transport = Arc::new(MockTransport::new());
+/// transport.push_response(TransportResponse::json(
+///     200,
+///     &json!({ "data": [{ "status": "ok", "id": "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX" }] }),
+/// ));
+///
+/// let client = ExpoNotificationsClient::new().transport(transport.clone());
+/// let token = PushToken::from_str("ExpoPushToken[my-token]").unwrap();
+/// let ticket = client
+///     .send_push_notification(&PushMessage::new(token).body("test notification"))
+///     .await
+///     .unwrap();
+///
+/// assert_eq!(transport.requests()[0].json()[0]["body"], "test notification");
+/// # });
+/// ```
+#[derive(Default)]
+pub struct MockTransport {
+    responses: Mutex<VecDeque<Result<TransportResponse, ExpoNotificationError>>>,
+    requests: Mutex<Vec<TransportRequest>>,
+    handler: Option<Box<Handler>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A mock answering every request that has no scripted response with `handler`.
+    pub fn with_handler(
+        handler: impl Fn(&TransportRequest) -> TransportResponse + Send + Sync + 'static,
+    ) -> Self {
+        MockTransport {
+            handler: Some(Box::new(handler)),
+            ..Self::default()
+        }
+    }
+
+    /// Queue a response for the next request.
+    pub fn push_response(&self, response: TransportResponse) {
+        self.responses.lock().unwrap().push_back(Ok(response));
+    }
+
+    /// Queue an error for the next request, such as an
+    /// [`ExpoNotificationError::Transport`] to simulate a network failure.
+    pub fn push_error(&self, error: ExpoNotificationError) {
+        self.responses.lock().unwrap().push_back(Err(error));
+    }
+
+    /// The requests sent so far.
+    pub fn requests(&self) -> Vec<TransportRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Transport for MockTransport {
+    async fn send(
+        &self,
+        request: TransportRequest,
+    ) -> Result<TransportResponse, ExpoNotificationError> {
+        let scripted = self.responses.lock().unwrap().pop_front();
+        let response = match (scripted, &self.handler) {
+            (Some(response), _) => response,
+            (None, Some(handler)) => Ok(handler(&request)),
+            (None, None) => panic!("MockTransport has no response left for {}", request.url),
+        };
+        self.requests.lock().unwrap().push(request);
+        response
+    }
+}
+
+impl TransportRequest {
+    /// The request body as JSON, decompressed if needed.
+    ///
+    /// Panics if the body is not valid (gzipped) JSON.
+    pub fn json(&self) -> Value {
+        if self.headers.contains_key(CONTENT_ENCODING) {
+            let mut body = Vec::new();
+            flate2::read::GzDecoder::new(&self.body[..])
+                .read_to_end(&mut body)
+                .unwrap();
+            serde_json::from_slice(&body).unwrap()
+        } else {
+            serde_json::from_slice(&self.body).unwrap()
+        }
+    }
+}
+
+impl TransportResponse {
+    /// A response with the given status and JSON body.
+    pub fn json(status: u16, body: &Value) -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        TransportResponse {
+            status: StatusCode::from_u16(status).unwrap(),
+            headers,
+            body: body.to_string().into(),
+        }
+    }
+
+    /// A response with the given status and an empty body.
+    pub fn status(status: u16) -> Self {
+        TransportResponse {
+            status: StatusCode::from_u16(status).unwrap(),
+            headers: HeaderMap::new(),
+            body: Default::default(),
+        }
+    }
+}
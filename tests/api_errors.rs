@@ -0,0 +1,101 @@
+mod common;
+
+use std::str::FromStr;
+
+use common::{Scripted, StandInServer};
+use expo_server_sdk::{
+    error::ExpoNotificationError,
+    message::{PushMessage, PushToken},
+    response::RequestErrorCode,
+    ExpoNotificationsClient, RetryPolicy,
+};
+use serde_json::json;
+
+fn create_push_message() -> PushMessage {
+    PushMessage::new(PushToken::from_str("ExponentPushToken[xxxxxxxxxxxxxxxxxxxxxx]").unwrap())
+        .title("hello")
+}
+
+fn create_client(server: &StandInServer) -> ExpoNotificationsClient {
+    ExpoNotificationsClient::new()
+        .push_url(server.url("/push/send").parse().unwrap())
+        .retry_policy(RetryPolicy::never())
+}
+
+#[tokio::test]
+async fn parses_request_errors() {
+    let server = StandInServer::start([Scripted::json(
+        400,
+        json!({
+            "errors": [{
+                "code": "PUSH_TOO_MANY_EXPERIENCE_IDS",
+                "message": "All push notification messages in the same request must be for the same project; check the details field to investigate conflicting tokens.",
+                "details": {
+                    "@alice/app": ["ExponentPushToken[xxxxxxxxxxxxxxxxxxxxxx]"],
+                    "@bob/app": ["ExponentPushToken[yyyyyyyyyyyyyyyyyyyyyy]"]
+                }
+            }]
+        })
+        .to_string(),
+    )]);
+    let client = create_client(&server);
+
+    match client.send_push_notification(&create_push_message()).await {
+        Err(ExpoNotificationError::Api { status, errors }) => {
+            assert_eq!(status, 400);
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].code, RequestErrorCode::PushTooManyExperienceIds);
+            let details = errors[0].details.as_ref().unwrap();
+            assert_eq!(
+                details["@bob/app"][0],
+                "ExponentPushToken[yyyyyyyyyyyyyyyyyyyyyy]"
+            );
+        }
+        other => panic!("expected a request error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn keeps_unknown_error_codes() {
+    let server = StandInServer::start([Scripted::json(
+        429,
+        json!({
+            "errors": [{ "code": "RATE_LIMIT_EXCEEDED", "message": "slow down" }]
+        })
+        .to_string(),
+    )]);
+    let client = create_client(&server);
+
+    let err = client
+        .send_push_notification(&create_push_message())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "request rejected with status 429 Too Many Requests, RATE_LIMIT_EXCEEDED: slow down"
+    );
+    match err {
+        ExpoNotificationError::Api { errors, .. } => {
+            assert_eq!(
+                errors[0].code,
+                RequestErrorCode::Other("RATE_LIMIT_EXCEEDED".to_owned())
+            );
+            assert!(errors[0].details.is_none());
+        }
+        other => panic!("expected a request error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn tolerates_bodies_without_errors() {
+    let server = StandInServer::start([Scripted::status(502)]);
+    let client = create_client(&server);
+
+    match client.send_push_notification(&create_push_message()).await {
+        Err(ExpoNotificationError::Api { status, errors }) => {
+            assert_eq!(status, 502);
+            assert!(errors.is_empty());
+        }
+        other => panic!("expected a request error, got {other:?}"),
+    }
+}
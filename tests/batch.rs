@@ -0,0 +1,60 @@
+mod common;
+
+use common::{create_push_messages, echo_tickets, Scripted, StandInServer};
+use expo_server_sdk::{ExpoNotificationsClient, RetryPolicy};
+
+/// A server rejecting the chunk starting with message 10.
+fn create_server() -> StandInServer {
+    StandInServer::with_handler(|req| {
+        if req.json()[0]["body"] == "10" {
+            Scripted::status(400)
+        } else {
+            echo_tickets(req)
+        }
+    })
+}
+
+fn create_client(server: &StandInServer) -> ExpoNotificationsClient {
+    ExpoNotificationsClient::new()
+        .push_url(server.url("/push/send").parse().unwrap())
+        .retry_policy(RetryPolicy::never())
+        .push_chunk_size(10)
+}
+
+#[tokio::test]
+async fn reports_each_chunk() {
+    let server = create_server();
+    let client = create_client(&server);
+
+    let result = client
+        .send_push_notifications_batch(create_push_messages(25))
+        .await;
+    assert_eq!(server.request_count(), 3);
+    assert!(!result.is_complete());
+
+    let ranges = result
+        .chunks
+        .iter()
+        .map(|chunk| chunk.messages.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(ranges, vec![0..10, 10..20, 20..25]);
+
+    let delivered = result.tickets().map(|(i, _, _)| i).collect::<Vec<_>>();
+    assert_eq!(delivered, (0..10).chain(20..25).collect::<Vec<_>>());
+
+    let failed = result.failed_messages().collect::<Vec<_>>();
+    assert_eq!(failed, (10..20).collect::<Vec<_>>());
+    assert!(result.into_result().is_err());
+}
+
+#[tokio::test]
+async fn send_push_notifications_stops_at_failed_chunk() {
+    let server = create_server();
+    let client = create_client(&server);
+
+    let result = client
+        .send_push_notifications(create_push_messages(25))
+        .await;
+    assert!(result.is_err());
+    assert_eq!(server.request_count(), 2);
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::{create_push_messages, echo_transport};
+use expo_server_sdk::{
+    blocking::ExpoNotificationsClient,
+    response::{PushReceipt, PushTicket},
+};
+
+#[test]
+fn sends_and_gets_receipts_without_a_runtime() {
+    let transport = echo_transport();
+    let client: ExpoNotificationsClient = expo_server_sdk::ExpoNotificationsClient::new()
+        .transport(transport.clone())
+        .push_chunk_size(10)
+        .into();
+
+    let tickets = client
+        .send_push_notifications(create_push_messages(25))
+        .unwrap();
+    assert_eq!(tickets.len(), 25);
+    assert_eq!(transport.requests().len(), 3);
+
+    let ids = tickets.iter().map(|ticket| ticket.receipt_id().unwrap());
+    let receipts = client.get_push_receipts(ids).unwrap();
+    assert_eq!(receipts.len(), 25);
+    assert!(receipts
+        .values()
+        .all(|receipt| matches!(receipt, PushReceipt::Ok {})));
+}
+
+#[test]
+fn sends_a_single_message() {
+    let client: ExpoNotificationsClient = expo_server_sdk::ExpoNotificationsClient::new()
+        .transport(echo_transport())
+        .into();
+
+    let ticket = client
+        .send_push_notification(&create_push_messages(1)[0])
+        .unwrap();
+    assert!(matches!(ticket, PushTicket::Ok { .. }));
+}
@@ -0,0 +1,252 @@
+//! A local stand-in for the Expo push servers, answering with scripted responses.
+
+#![allow(dead_code)]
+
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    io::Read,
+    net::SocketAddr,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use expo_server_sdk::message::{PushMessage, PushToken};
+#[cfg(feature = "testing")]
+use expo_server_sdk::transport::{MockTransport, TransportRequest, TransportResponse};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+use serde_json::{json, Value};
+
+/// A response the stand-in server sends back.
+pub struct Scripted {
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: String,
+    pub delay: Duration,
+}
+
+impl Scripted {
+    pub fn status(status: u16) -> Self {
+        Scripted {
+            status,
+            headers: Vec::new(),
+            body: String::new(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn json(status: u16, body: impl Into<String>) -> Self {
+        Scripted {
+            body: body.into(),
+            ..Scripted::status(status).header("content-type", "application/json")
+        }
+    }
+
+    pub fn header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// A request received by the stand-in server.
+#[derive(Clone)]
+pub struct Received {
+    pub path: String,
+    pub headers: hyper::HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl Received {
+    /// The request body as JSON, decompressed if it was gzipped.
+    pub fn json(&self) -> Value {
+        if self.headers.get("content-encoding").is_some() {
+            let mut decoded = Vec::new();
+            flate2::read::GzDecoder::new(&self.body[..])
+                .read_to_end(&mut decoded)
+                .unwrap();
+            serde_json::from_slice(&decoded).unwrap()
+        } else {
+            serde_json::from_slice(&self.body).unwrap()
+        }
+    }
+}
+
+type Handler = dyn Fn(&Received) -> Scripted + Send + Sync;
+
+pub struct StandInServer {
+    pub addr: SocketAddr,
+    received: Arc<Mutex<Vec<Received>>>,
+    in_flight: Arc<InFlight>,
+}
+
+/// Counts the requests being answered, and the most there were at once.
+#[derive(Default)]
+struct InFlight {
+    current: AtomicUsize,
+    max: AtomicUsize,
+}
+
+impl StandInServer {
+    /// Start a server answering with the given responses, in order.
+    /// Once the script is exhausted every request gets a `500`.
+    pub fn start(script: impl IntoIterator<Item = Scripted>) -> Self {
+        let script = Mutex::new(script.into_iter().collect::<VecDeque<_>>());
+        Self::with_handler(move |_| {
+            script
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or_else(|| Scripted::status(500))
+        })
+    }
+
+    /// Start a server answering each request with the response built by `handler`.
+    pub fn with_handler(handler: impl Fn(&Received) -> Scripted + Send + Sync + 'static) -> Self {
+        let handler: Arc<Handler> = Arc::new(handler);
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let in_flight = Arc::new(InFlight::default());
+
+        let make_svc = {
+            let received = received.clone();
+            let in_flight = in_flight.clone();
+            make_service_fn(move |_| {
+                let handler = handler.clone();
+                let received = received.clone();
+                let in_flight = in_flight.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                        let handler = handler.clone();
+                        let received = received.clone();
+                        let in_flight = in_flight.clone();
+                        async move {
+                            let current = in_flight.current.fetch_add(1, Ordering::SeqCst) + 1;
+                            in_flight.max.fetch_max(current, Ordering::SeqCst);
+                            let (parts, body) = req.into_parts();
+                            let body = hyper::body::to_bytes(body).await.unwrap();
+                            let req = Received {
+                                path: parts.uri.path().to_owned(),
+                                headers: parts.headers,
+                                body: body.to_vec(),
+                            };
+                            let scripted = handler(&req);
+                            received.lock().unwrap().push(req);
+                            tokio::time::sleep(scripted.delay).await;
+                            in_flight.current.fetch_sub(1, Ordering::SeqCst);
+
+                            let mut res = Response::builder()
+                                .status(StatusCode::from_u16(scripted.status).unwrap());
+                            for (name, value) in scripted.headers {
+                                res = res.header(name, value);
+                            }
+                            Ok::<_, Infallible>(res.body(Body::from(scripted.body)).unwrap())
+                        }
+                    }))
+                }
+            })
+        };
+
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+
+        StandInServer {
+            addr,
+            received,
+            in_flight,
+        }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.received.lock().unwrap().len()
+    }
+
+    /// The most requests that were being answered at the same time.
+    pub fn max_in_flight(&self) -> usize {
+        self.in_flight.max.load(Ordering::SeqCst)
+    }
+
+    pub fn take_received(&self) -> Vec<Received> {
+        std::mem::take(&mut *self.received.lock().unwrap())
+    }
+}
+
+pub const OK_TICKET: &str =
+    r#"{"data":[{"status":"ok","id":"XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX"}]}"#;
+
+/// `n` messages to the same token, each with its position as the body.
+pub fn create_push_messages(n: usize) -> Vec<PushMessage> {
+    let token = PushToken::from_str("ExponentPushToken[xxxxxxxxxxxxxxxxxxxxxx]").unwrap();
+    (0..n)
+        .map(|i| PushMessage::new(token.clone()).body(i.to_string()))
+        .collect()
+}
+
+/// Answer a push request with one `ok` ticket per recipient, using the message body as the id,
+/// followed by the position of the recipient for messages with several of them.
+pub fn echo_tickets(req: &Received) -> Scripted {
+    Scripted::json(200, echo_ticket_data(&req.json()).to_string())
+}
+
+/// Answer a receipts request with an `ok` receipt for every id.
+pub fn echo_receipts(req: &Received) -> Scripted {
+    Scripted::json(200, echo_receipt_data(&req.json()).to_string())
+}
+
+/// A [`MockTransport`] answering like `echo_tickets` and `echo_receipts`.
+#[cfg(feature = "testing")]
+pub fn echo_transport() -> Arc<MockTransport> {
+    Arc::new(MockTransport::with_handler(echo))
+}
+
+/// Answer a push or receipts request like `echo_tickets` or `echo_receipts`.
+#[cfg(feature = "testing")]
+pub fn echo(req: &TransportRequest) -> TransportResponse {
+    let body = req.json();
+    let data = if body.is_array() {
+        echo_ticket_data(&body)
+    } else {
+        echo_receipt_data(&body)
+    };
+    TransportResponse::json(200, &data)
+}
+
+fn echo_ticket_data(body: &Value) -> Value {
+    let tickets = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|msg| match msg["to"].as_array() {
+            Some(tokens) => (0..tokens.len())
+                .map(|i| json!({ "status": "ok", "id": format!("{}/{i}", msg["body"].as_str().unwrap()) }))
+                .collect(),
+            None => vec![json!({ "status": "ok", "id": msg["body"] })],
+        })
+        .collect::<Vec<_>>();
+    json!({ "data": tickets })
+}
+
+fn echo_receipt_data(body: &Value) -> Value {
+    let receipts = body["ids"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|id| (id.as_str().unwrap().to_owned(), json!({ "status": "ok" })))
+        .collect::<serde_json::Map<_, _>>();
+    json!({ "data": receipts })
+}
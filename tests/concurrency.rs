@@ -0,0 +1,64 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{create_push_messages, echo_receipts, echo_tickets, StandInServer};
+use expo_server_sdk::{
+    response::{PushReceiptId, PushTicket},
+    ExpoNotificationsClient,
+};
+
+#[tokio::test]
+async fn sends_chunks_concurrently_in_order() {
+    // The first chunk is the slowest to answer, the last one the fastest.
+    let server = StandInServer::with_handler(|req| {
+        let first = req.json()[0]["body"]
+            .as_str()
+            .unwrap()
+            .parse::<u64>()
+            .unwrap();
+        echo_tickets(req).delay(Duration::from_millis(300 - first * 10))
+    });
+    let client = ExpoNotificationsClient::new()
+        .push_url(server.url("/push/send").parse().unwrap())
+        .push_chunk_size(10)
+        .max_in_flight_chunks(3);
+
+    let tickets = client
+        .send_push_notifications(create_push_messages(30))
+        .await
+        .unwrap();
+    assert_eq!(server.request_count(), 3);
+    assert_eq!(server.max_in_flight(), 3);
+
+    let ids = tickets
+        .into_iter()
+        .map(|ticket| match ticket {
+            PushTicket::Ok { id } => id,
+            PushTicket::Error { message, .. } => panic!("unexpected error ticket {message}"),
+        })
+        .collect::<Vec<_>>();
+    let expected = (0..30)
+        .map(|i| serde_json::from_value::<PushReceiptId>(i.to_string().into()).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, expected);
+}
+
+#[tokio::test]
+async fn gets_receipt_chunks_concurrently() {
+    let server =
+        StandInServer::with_handler(|req| echo_receipts(req).delay(Duration::from_millis(200)));
+    let client = ExpoNotificationsClient::new()
+        .receipt_url(server.url("/push/getReceipts").parse().unwrap())
+        .receipt_chunk_size(10)
+        .max_in_flight_chunks(4);
+
+    let ids = (0..40)
+        .map(|i| serde_json::from_value::<PushReceiptId>(i.to_string().into()).unwrap())
+        .collect::<Vec<_>>();
+
+    let receipts = client.get_push_receipts(&ids).await.unwrap();
+    assert_eq!(server.request_count(), 4);
+    assert_eq!(server.max_in_flight(), 4);
+    assert!(ids.iter().all(|id| receipts.contains_key(id)));
+}
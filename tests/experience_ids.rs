@@ -0,0 +1,191 @@
+mod common;
+
+use std::str::FromStr;
+
+use common::{echo_tickets, Scripted, StandInServer};
+use expo_server_sdk::{
+    error::ExpoNotificationError,
+    message::{PushMessage, PushToken},
+    response::PushTicket,
+    ExpoNotificationsClient,
+};
+use serde_json::{json, Value};
+
+const ALICE_TOKEN: &str = "ExponentPushToken[aaaaaaaaaaaaaaaaaaaaaa]";
+const BOB_TOKEN: &str = "ExponentPushToken[bbbbbbbbbbbbbbbbbbbbbb]";
+
+/// A server refusing requests with tokens of both projects.
+fn create_server() -> StandInServer {
+    create_server_refusing(|_| false)
+}
+
+/// A server refusing requests with tokens of both projects, and those with tokens for which
+/// `refuse` is true.
+fn create_server_refusing(refuse: fn(&str) -> bool) -> StandInServer {
+    StandInServer::with_handler(move |req| {
+        let body = req.json();
+        let tokens = body
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|msg| match &msg["to"] {
+                Value::Array(tokens) => tokens.clone(),
+                token => vec![token.clone()],
+            })
+            .collect::<Vec<_>>();
+        let project_tokens = |token: &str| {
+            tokens
+                .iter()
+                .filter(|t| *t == token)
+                .cloned()
+                .collect::<Vec<Value>>()
+        };
+        let (alice, bob) = (project_tokens(ALICE_TOKEN), project_tokens(BOB_TOKEN));
+        if !alice.is_empty() && !bob.is_empty() {
+            Scripted::json(
+                400,
+                json!({
+                    "errors": [{
+                        "code": "PUSH_TOO_MANY_EXPERIENCE_IDS",
+                        "message": "All push notification messages in the same request must be for the same project; check the details field to investigate conflicting tokens.",
+                        "details": { "@alice/app": alice, "@bob/app": bob }
+                    }]
+                })
+                .to_string(),
+            )
+        } else if tokens.iter().any(|token| refuse(token.as_str().unwrap())) {
+            Scripted::status(400)
+        } else {
+            echo_tickets(req)
+        }
+    })
+}
+
+fn create_client(server: &StandInServer) -> ExpoNotificationsClient {
+    ExpoNotificationsClient::new().push_url(server.url("/push/send").parse().unwrap())
+}
+
+fn ticket_id(ticket: &PushTicket) -> String {
+    serde_json::to_value(ticket.receipt_id().unwrap())
+        .unwrap()
+        .as_str()
+        .unwrap()
+        .to_owned()
+}
+
+#[tokio::test]
+async fn splits_chunks_by_project() {
+    let server = create_server();
+    let client = create_client(&server);
+
+    let messages = (0..7).map(|i| {
+        let token = if i % 3 == 0 { BOB_TOKEN } else { ALICE_TOKEN };
+        PushMessage::new(PushToken::from_str(token).unwrap()).body(i.to_string())
+    });
+    let tickets = client.send_push_notifications(messages).await.unwrap();
+
+    let ids = tickets.iter().map(ticket_id).collect::<Vec<_>>();
+    assert_eq!(ids, ["0", "1", "2", "3", "4", "5", "6"]);
+
+    let requests = server.take_received();
+    assert_eq!(requests.len(), 3);
+    let bodies = requests[1..]
+        .iter()
+        .map(|req| {
+            req.json()
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|msg| msg["body"].as_str().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(bodies, [vec!["0", "3", "6"], vec!["1", "2", "4", "5"]]);
+}
+
+#[tokio::test]
+async fn splits_messages_with_recipients_of_both_projects() {
+    let server = create_server();
+    let client = create_client(&server);
+
+    let token = |token| PushToken::from_str(token).unwrap();
+    let messages = [
+        PushMessage::with_recipients([token(ALICE_TOKEN), token(BOB_TOKEN), token(ALICE_TOKEN)])
+            .body("0"),
+        PushMessage::new(token(BOB_TOKEN)).body("1"),
+    ];
+    let tickets = client.send_push_notifications(&messages).await.unwrap();
+
+    let ids = tickets.iter().map(ticket_id).collect::<Vec<_>>();
+    assert_eq!(ids, ["0/0", "0", "0/1", "1"]);
+
+    let requests = server.take_received();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(
+        requests[1].json(),
+        json!([{ "to": [ALICE_TOKEN, ALICE_TOKEN], "body": "0" }])
+    );
+    assert_eq!(
+        requests[2].json(),
+        json!([{ "to": BOB_TOKEN, "body": "0" }, { "to": BOB_TOKEN, "body": "1" }])
+    );
+}
+
+#[tokio::test]
+async fn single_project_errors_are_returned() {
+    let server = StandInServer::start([Scripted::json(
+        400,
+        json!({
+            "errors": [{
+                "code": "PUSH_TOO_MANY_EXPERIENCE_IDS",
+                "message": "All push notification messages in the same request must be for the same project",
+                "details": { "@alice/app": [ALICE_TOKEN] }
+            }]
+        })
+        .to_string(),
+    )]);
+    let client = create_client(&server);
+
+    let message = PushMessage::new(PushToken::from_str(ALICE_TOKEN).unwrap());
+    let result = client.send_push_notification(&message).await;
+    assert!(matches!(result, Err(ExpoNotificationError::Api { .. })));
+    assert_eq!(server.request_count(), 1);
+}
+
+#[tokio::test]
+async fn keeps_the_tickets_of_accepted_groups() {
+    let server = create_server_refusing(|token| token == BOB_TOKEN);
+    let client = create_client(&server);
+
+    let token = |token| PushToken::from_str(token).unwrap();
+    let messages = [
+        PushMessage::new(token(ALICE_TOKEN)).body("0"),
+        PushMessage::with_recipients([token(ALICE_TOKEN), token(BOB_TOKEN)]).body("1"),
+        PushMessage::new(token(ALICE_TOKEN)).body("2"),
+    ];
+    let result = client.send_push_notifications_batch(&messages).await;
+    assert_eq!(server.request_count(), 3);
+    assert!(matches!(
+        result.chunks[0].result,
+        Err(ExpoNotificationError::PartiallySent { .. })
+    ));
+
+    let delivered = result
+        .tickets()
+        .map(|(i, _, ticket)| (i, ticket_id(ticket)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        delivered,
+        [
+            (0, "0".to_owned()),
+            (1, "1".to_owned()),
+            (2, "2".to_owned())
+        ]
+    );
+    let failed = result
+        .failed_recipients()
+        .map(|(i, token)| (i, token.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(failed, [(1, token(BOB_TOKEN))]);
+    assert_eq!(result.failed_messages().collect::<Vec<_>>(), [1]);
+}
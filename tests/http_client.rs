@@ -0,0 +1,68 @@
+mod common;
+
+use std::{str::FromStr, time::Duration};
+
+use common::{Scripted, StandInServer, OK_TICKET};
+use expo_server_sdk::{
+    error::ExpoNotificationError,
+    message::{PushMessage, PushToken},
+    ExpoNotificationsClient, RetryPolicy,
+};
+
+fn create_push_message() -> PushMessage {
+    PushMessage::new(PushToken::from_str("ExponentPushToken[xxxxxxxxxxxxxxxxxxxxxx]").unwrap())
+        .title("hello")
+}
+
+#[tokio::test]
+async fn sends_through_the_given_client() {
+    let server = StandInServer::start([Scripted::json(200, OK_TICKET)]);
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("x-egress", "proxy".parse().unwrap());
+    let http_client = reqwest::Client::builder()
+        .default_headers(headers)
+        .build()
+        .unwrap();
+    let client = ExpoNotificationsClient::new()
+        .push_url(server.url("/push/send").parse().unwrap())
+        .http_client(http_client);
+
+    client
+        .send_push_notification(&create_push_message())
+        .await
+        .unwrap();
+    assert_eq!(server.take_received()[0].headers["x-egress"], "proxy");
+}
+
+#[tokio::test]
+async fn times_out_slow_responses() {
+    let server =
+        StandInServer::start([Scripted::json(200, OK_TICKET).delay(Duration::from_secs(5))]);
+    let client = ExpoNotificationsClient::new()
+        .push_url(server.url("/push/send").parse().unwrap())
+        .connect_timeout(Duration::from_secs(1))
+        .timeout(Duration::from_millis(100))
+        .retry_policy(RetryPolicy::never());
+
+    let result = client.send_push_notification(&create_push_message()).await;
+    match result {
+        Err(ExpoNotificationError::Request(e)) => assert!(e.is_timeout()),
+        other => panic!("expected a timeout, got {other:?}"),
+    }
+}
+
+#[test]
+#[should_panic(expected = "connect_timeout only applies to the default transport")]
+fn rejects_a_connect_timeout_after_a_client() {
+    let _ = ExpoNotificationsClient::new()
+        .http_client(reqwest::Client::new())
+        .connect_timeout(Duration::from_secs(1));
+}
+
+#[test]
+#[should_panic(expected = "connect_timeout only applies to the default transport")]
+fn rejects_a_client_after_a_connect_timeout() {
+    let _ = ExpoNotificationsClient::new()
+        .connect_timeout(Duration::from_secs(1))
+        .http_client(reqwest::Client::new());
+}
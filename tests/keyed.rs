@@ -0,0 +1,96 @@
+mod common;
+
+use std::str::FromStr;
+
+use common::{echo_tickets, Scripted, StandInServer};
+use expo_server_sdk::{
+    message::{PushMessage, PushToken},
+    response::PushReceipt,
+    ExpoNotificationsClient,
+};
+use serde_json::json;
+
+#[tokio::test]
+async fn tickets_and_receipts_keep_their_keys() {
+    let server = StandInServer::with_handler(|req| {
+        if req.path.ends_with("send") {
+            echo_tickets(req)
+        } else {
+            // Receipt 3 is not available yet.
+            let receipts = req.json()["ids"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|id| *id != "3")
+                .map(|id| (id.as_str().unwrap().to_owned(), json!({ "status": "ok" })))
+                .collect::<serde_json::Map<_, _>>();
+            Scripted::json(200, json!({ "data": receipts }).to_string())
+        }
+    });
+    let client = ExpoNotificationsClient::new()
+        .push_url(server.url("/push/send").parse().unwrap())
+        .receipt_url(server.url("/push/getReceipts").parse().unwrap())
+        .push_chunk_size(2)
+        .max_in_flight_chunks(2);
+
+    let token = PushToken::from_str("ExponentPushToken[xxxxxxxxxxxxxxxxxxxxxx]").unwrap();
+    let users = ["ada", "grace", "linus", "ken", "dennis"];
+    let messages = users
+        .iter()
+        .enumerate()
+        .map(|(i, user)| (*user, PushMessage::new(token.clone()).body(i.to_string())));
+
+    let tickets = client
+        .send_push_notifications_keyed(messages)
+        .await
+        .unwrap();
+    let keys = tickets.iter().map(|(user, _, _)| *user).collect::<Vec<_>>();
+    assert_eq!(keys, users);
+
+    let ids = tickets
+        .iter()
+        .map(|(user, _, ticket)| (*user, ticket.receipt_id().unwrap()));
+    let receipts = client.get_push_receipts_keyed(ids).await.unwrap();
+    let delivered = receipts
+        .iter()
+        .map(|(user, receipt)| (*user, matches!(receipt, Some(PushReceipt::Ok {}))))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        delivered,
+        vec![
+            ("ada", true),
+            ("grace", true),
+            ("linus", true),
+            ("ken", false),
+            ("dennis", true)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn error_tickets_have_no_receipt_id() {
+    let server = StandInServer::start([Scripted::json(
+        200,
+        json!({
+            "data": [{
+                "status": "error",
+                "message": "\"ExponentPushToken[xxxxxxxxxxxxxxxxxxxxxx]\" is not a registered push notification recipient",
+                "details": {
+                    "error": "DeviceNotRegistered",
+                    "expoPushToken": "ExponentPushToken[xxxxxxxxxxxxxxxxxxxxxx]"
+                }
+            }]
+        })
+        .to_string(),
+    )]);
+    let client = ExpoNotificationsClient::new().push_url(server.url("/push/send").parse().unwrap());
+
+    let token = PushToken::from_str("ExponentPushToken[xxxxxxxxxxxxxxxxxxxxxx]").unwrap();
+    let tickets = client
+        .send_push_notifications_keyed([("ada", PushMessage::new(token))])
+        .await
+        .unwrap();
+    assert_eq!(tickets.len(), 1);
+    assert_eq!(tickets[0].0, "ada");
+    assert!(tickets[0].2.receipt_id().is_none());
+}
@@ -0,0 +1,246 @@
+use std::{
+    str::FromStr,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use expo_server_sdk::message::{
+    CriticalSound, InterruptionLevel, Priority, PushDataError, PushMessage, PushToken, Sound,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+const TOKEN: &str = "ExponentPushToken[xxxxxxxxxxxxxxxxxxxxxx]";
+
+fn create_push_message() -> PushMessage {
+    PushMessage::new(PushToken::from_str(TOKEN).unwrap())
+}
+
+#[test]
+fn serializes_unset_fields_out() {
+    let message = serde_json::to_value(create_push_message()).unwrap();
+    assert_eq!(message, json!({ "to": TOKEN }));
+}
+
+#[test]
+fn serializes_fields_in_camel_case() {
+    let message = create_push_message()
+        .title("title")
+        .subtitle("subtitle")
+        .channel_id("alerts")
+        .category_id("reply")
+        .mutable_content(true)
+        .content_available(true)
+        .interruption_level(InterruptionLevel::TimeSensitive)
+        .image("https://example.com/image.png");
+
+    assert_eq!(
+        serde_json::to_value(message).unwrap(),
+        json!({
+            "to": TOKEN,
+            "title": "title",
+            "subtitle": "subtitle",
+            "channelId": "alerts",
+            "categoryId": "reply",
+            "mutableContent": true,
+            "_contentAvailable": true,
+            "interruptionLevel": "time-sensitive",
+            "richContent": { "image": "https://example.com/image.png" }
+        })
+    );
+}
+
+#[test]
+fn serializes_sounds() {
+    let sound =
+        |sound| serde_json::to_value(create_push_message().sound(sound)).unwrap()["sound"].clone();
+
+    assert_eq!(sound(Sound::Default), json!("default"));
+    assert_eq!(
+        sound(Sound::Custom("ping.wav".to_owned())),
+        json!("ping.wav")
+    );
+    assert_eq!(
+        sound(Sound::Critical(
+            CriticalSound::new().name("alarm.wav").volume(0.5).unwrap()
+        )),
+        json!({ "critical": true, "name": "alarm.wav", "volume": 0.5 })
+    );
+}
+
+#[test]
+fn parses_sounds() {
+    assert!(matches!(Sound::from_str("default"), Ok(Sound::Default)));
+    assert!(matches!(Sound::from_str("\"default\""), Ok(Sound::Default)));
+    assert!(matches!(Sound::from_str("ping.wav"), Ok(Sound::Custom(name)) if name == "ping.wav"));
+
+    let Ok(Sound::Critical(critical)) = Sound::from_str(r#"{"critical":true,"volume":1}"#) else {
+        panic!("expected a critical sound");
+    };
+    assert!(critical.is_critical());
+    assert_eq!(critical.get_name(), None);
+    assert_eq!(critical.get_volume(), Some(1.0));
+}
+
+#[test]
+fn rejects_volumes_out_of_range() {
+    assert!(CriticalSound::new().volume(1.5).is_err());
+    assert!(CriticalSound::new().volume(-0.1).is_err());
+    assert!(CriticalSound::new().volume(f64::NAN).is_err());
+    assert!(Sound::from_str(r#"{"critical":true,"volume":2}"#).is_err());
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChatData {
+    room_id: u64,
+    sender: String,
+}
+
+#[test]
+fn round_trips_typed_data() {
+    let data = ChatData {
+        room_id: 42,
+        sender: "ada".to_owned(),
+    };
+    let message = create_push_message().try_data(&data).unwrap();
+
+    assert_eq!(
+        serde_json::to_value(&message).unwrap()["data"],
+        json!({ "roomId": 42, "sender": "ada" })
+    );
+    assert_eq!(message.parse_data::<ChatData>().unwrap(), Some(data));
+    assert!(create_push_message()
+        .parse_data::<ChatData>()
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn rejects_data_that_is_not_an_object() {
+    let result = create_push_message().try_data(&[1, 2, 3]);
+    assert!(matches!(result, Err(PushDataError::NotAnObject(_))));
+
+    let result = create_push_message().try_data(&"text");
+    assert!(matches!(result, Err(PushDataError::NotAnObject(_))));
+}
+
+#[test]
+fn serializes_ttl_and_expiration_in_seconds() {
+    let message = create_push_message().ttl_duration(Duration::from_millis(90_500));
+    assert_eq!(serde_json::to_value(message).unwrap()["ttl"], json!(91));
+    let message = create_push_message().ttl_duration(Duration::from_millis(500));
+    assert_eq!(message.ttl, Some(Duration::from_secs(1)));
+
+    let expiration = UNIX_EPOCH + Duration::from_secs(5_000_000_000);
+    let message = create_push_message().expires_at(expiration);
+    // Past what a u32 timestamp can hold.
+    assert_eq!(
+        serde_json::to_value(message).unwrap()["expiration"],
+        json!(5_000_000_000u64)
+    );
+
+    let message = create_push_message().ttl(60).expiration(1_700_000_000);
+    assert_eq!(message.ttl, Some(Duration::from_secs(60)));
+    assert_eq!(
+        message.expiration,
+        Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+    );
+}
+
+#[test]
+fn rounds_up_fields_set_directly() {
+    let mut message = create_push_message();
+    message.ttl = Some(Duration::from_millis(500));
+    message.expiration = Some(UNIX_EPOCH + Duration::from_millis(1_700_000_000_250));
+    let json = serde_json::to_value(message).unwrap();
+    assert_eq!(json["ttl"], json!(1));
+    assert_eq!(json["expiration"], json!(1_700_000_001));
+}
+
+#[test]
+fn round_trips_sub_second_expirations() {
+    let message = create_push_message().expires_in(Duration::from_millis(1_500));
+    let expiration = message.expiration.unwrap();
+    assert_eq!(
+        expiration
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .subsec_nanos(),
+        0
+    );
+
+    let json = serde_json::to_string(&message).unwrap();
+    assert_eq!(serde_json::from_str::<PushMessage>(&json).unwrap(), message);
+}
+
+#[test]
+fn rejects_expirations_before_the_epoch() {
+    let message = create_push_message().expires_at(UNIX_EPOCH - Duration::from_secs(1));
+    assert!(serde_json::to_value(message).is_err());
+}
+
+fn round_trip(message: &PushMessage) -> PushMessage {
+    let json = serde_json::to_string(message).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+#[test]
+fn round_trips_every_field() {
+    let message = PushMessage::with_recipients([
+        PushToken::from_str(TOKEN).unwrap(),
+        PushToken::from_str("ExpoPushToken[yyyyyyyyyyyyyyyyyyyyyy]").unwrap(),
+    ])
+    .data(json!({ "roomId": 42 }))
+    .title("title")
+    .subtitle("subtitle")
+    .body("body")
+    .sound(Sound::Critical(
+        CriticalSound::new().name("alarm.wav").volume(0.25).unwrap(),
+    ))
+    .ttl(3600)
+    .expires_at(UNIX_EPOCH + Duration::from_secs(5_000_000_000))
+    .priority(Priority::High)
+    .badge(3)
+    .channel_id("alerts")
+    .category_id("reply")
+    .mutable_content(true)
+    .content_available(false)
+    .interruption_level(InterruptionLevel::Passive)
+    .image("https://example.com/image.png");
+    assert_eq!(round_trip(&message), message);
+
+    let message = create_push_message();
+    assert_eq!(round_trip(&message), message);
+
+    for sound in [Sound::Default, Sound::Custom("ping.wav".to_owned())] {
+        let message = create_push_message().sound(sound);
+        assert_eq!(round_trip(&message), message);
+    }
+}
+
+#[test]
+fn deserializes_a_fixture() {
+    let message: PushMessage = serde_json::from_value(json!({
+        "to": TOKEN,
+        "title": "hello",
+        "sound": "default",
+        "ttl": 60,
+        "priority": "normal",
+        "_contentAvailable": true
+    }))
+    .unwrap();
+    assert_eq!(
+        message,
+        create_push_message()
+            .title("hello")
+            .sound(Sound::Default)
+            .ttl(60)
+            .priority(Priority::Normal)
+            .content_available(true)
+    );
+
+    let invalid_token = serde_json::from_value::<PushMessage>(json!({ "to": "not-a-token" }));
+    assert!(invalid_token.is_err());
+    let missing_token = serde_json::from_value::<PushMessage>(json!({ "title": "hello" }));
+    assert!(missing_token.is_err());
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use common::{create_push_messages, echo_transport};
+use expo_server_sdk::{transport::MockTransport, ExpoNotificationsClient};
+use tokio::time::Instant;
+
+fn create_client(transport: Arc<MockTransport>) -> ExpoNotificationsClient {
+    ExpoNotificationsClient::new()
+        .transport(transport)
+        .push_chunk_size(10)
+}
+
+// The clock is paused, so the elapsed time is exactly the time spent waiting on the limiter.
+
+#[tokio::test(start_paused = true)]
+async fn paces_chunks_under_the_limit() {
+    let transport = echo_transport();
+    // The first two chunks use up the initial second worth of tokens,
+    // the third one has to wait for 10 more tokens.
+    let client = create_client(transport.clone()).rate_limit(Some(20));
+
+    let start = Instant::now();
+    client
+        .send_push_notifications(create_push_messages(30))
+        .await
+        .unwrap();
+    assert_eq!(start.elapsed(), Duration::from_millis(500));
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn unlimited_by_default() {
+    let transport = echo_transport();
+    let client = create_client(transport.clone());
+
+    let start = Instant::now();
+    client
+        .send_push_notifications(create_push_messages(30))
+        .await
+        .unwrap();
+    assert_eq!(start.elapsed(), Duration::ZERO);
+    assert_eq!(transport.requests().len(), 3);
+}
@@ -0,0 +1,156 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use expo_server_sdk::{
+    error::ExpoNotificationError,
+    response::{PushReceipt, PushReceiptId},
+    transport::{MockTransport, TransportResponse},
+    ExpoNotificationsClient, ReceiptPoller,
+};
+use serde_json::json;
+use tokio::sync::mpsc;
+
+fn receipt_id(id: &str) -> PushReceiptId {
+    serde_json::from_value(json!(id)).unwrap()
+}
+
+/// A server where receipt "a" is available right away, "b" from the second check on,
+/// and "c" never.
+fn create_transport() -> Arc<MockTransport> {
+    let checks = Mutex::new(HashMap::<String, u32>::new());
+    Arc::new(MockTransport::with_handler(move |req| {
+        let mut checks = checks.lock().unwrap();
+        let mut receipts = serde_json::Map::new();
+        for id in req.json()["ids"].as_array().unwrap() {
+            let id = id.as_str().unwrap().to_owned();
+            let count = checks.entry(id.clone()).or_default();
+            *count += 1;
+            if id == "a" || (id == "b" && *count >= 2) {
+                receipts.insert(id, json!({ "status": "ok" }));
+            }
+        }
+        TransportResponse::json(200, &json!({ "data": receipts }))
+    }))
+}
+
+fn create_poller(transport: Arc<MockTransport>) -> ReceiptPoller {
+    let client = ExpoNotificationsClient::new().transport(transport);
+    ReceiptPoller::new(Arc::new(client))
+        .delay(Duration::from_millis(50))
+        .retry_delay(Duration::from_millis(20))
+        .max_attempts(3)
+}
+
+#[tokio::test]
+async fn waits_for_the_delay() {
+    let transport = create_transport();
+    let mut poller = create_poller(transport.clone()).delay(Duration::from_secs(60));
+
+    let sent_at = SystemTime::now();
+    poller.enqueue(receipt_id("a"), sent_at);
+    assert_eq!(poller.next_due(), Some(sent_at + Duration::from_secs(60)));
+    assert!(poller.poll_due().await.is_empty());
+    assert!(transport.requests().is_empty());
+    assert_eq!(poller.len(), 1);
+}
+
+#[tokio::test]
+async fn requeues_unavailable_receipts() {
+    let transport = create_transport();
+    let poller = create_poller(transport.clone());
+
+    let (ids_tx, ids_rx) = mpsc::channel(10);
+    let (receipts_tx, mut receipts_rx) = mpsc::channel(10);
+    let run = tokio::spawn(poller.run(ids_rx, receipts_tx));
+
+    let sent_at = SystemTime::now();
+    for id in ["a", "b", "c"] {
+        ids_tx.send((receipt_id(id), sent_at)).await.unwrap();
+    }
+    drop(ids_tx);
+
+    let mut outcomes = Vec::new();
+    while let Some(polled) = receipts_rx.recv().await {
+        let available = matches!(polled.receipt, Some(PushReceipt::Ok {}));
+        outcomes.push((polled.id, available));
+    }
+    run.await.unwrap();
+
+    assert!(SystemTime::now().duration_since(sent_at).unwrap() >= Duration::from_millis(50));
+    assert_eq!(
+        outcomes,
+        [
+            (receipt_id("a"), true),
+            (receipt_id("b"), true),
+            (receipt_id("c"), false)
+        ]
+    );
+    // "c" was checked three times before giving up.
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn reports_failed_requests() {
+    let transport = Arc::new(MockTransport::with_handler(|_| {
+        TransportResponse::status(400)
+    }));
+    let mut poller = create_poller(transport.clone())
+        .delay(Duration::ZERO)
+        .max_attempts(1);
+
+    poller.enqueue(receipt_id("a"), SystemTime::now());
+    let polled = poller.poll_due().await;
+    assert_eq!(polled.len(), 1);
+    assert!(polled[0].receipt.is_none());
+    assert!(matches!(
+        polled[0].error.as_deref(),
+        Some(ExpoNotificationError::Api { .. })
+    ));
+}
+
+#[tokio::test]
+async fn a_failed_chunk_only_affects_its_ids() {
+    let transport = Arc::new(MockTransport::with_handler(|req| {
+        if req.json()["ids"][0] == "c" {
+            TransportResponse::status(400)
+        } else {
+            let receipts = req.json()["ids"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|id| (id.as_str().unwrap().to_owned(), json!({ "status": "ok" })))
+                .collect::<serde_json::Map<_, _>>();
+            TransportResponse::json(200, &json!({ "data": receipts }))
+        }
+    }));
+    let client = ExpoNotificationsClient::new()
+        .transport(transport.clone())
+        .receipt_chunk_size(1);
+    let mut poller = ReceiptPoller::new(Arc::new(client))
+        .delay(Duration::ZERO)
+        .max_attempts(1);
+
+    let sent_at = SystemTime::now();
+    for id in ["a", "c", "b"] {
+        poller.enqueue(receipt_id(id), sent_at);
+    }
+    let mut polled = poller
+        .poll_due()
+        .await
+        .into_iter()
+        .map(|polled| (polled.id, polled.receipt.is_some(), polled.error.is_some()))
+        .collect::<Vec<_>>();
+    polled.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        polled,
+        [
+            (receipt_id("a"), true, false),
+            (receipt_id("b"), true, false),
+            (receipt_id("c"), false, true)
+        ]
+    );
+    assert_eq!(transport.requests().len(), 3);
+}
@@ -0,0 +1,137 @@
+use std::{str::FromStr, sync::Arc};
+
+use expo_server_sdk::{
+    error::ExpoNotificationError,
+    message::{PushMessage, PushToken},
+    transport::{MockTransport, TransportResponse},
+    ExpoNotificationsClient,
+};
+use serde_json::json;
+
+fn token(i: usize) -> PushToken {
+    PushToken::from_str(&format!("ExponentPushToken[{i:0>22}]")).unwrap()
+}
+
+fn create_message(body: &str, recipients: usize) -> PushMessage {
+    PushMessage::with_recipients((0..recipients).map(token)).body(body)
+}
+
+/// Answers with one ticket per recipient, with the token as the id.
+fn create_transport() -> Arc<MockTransport> {
+    Arc::new(MockTransport::with_handler(|req| {
+        let tickets = req
+            .json()
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|msg| match msg["to"].as_array() {
+                Some(tokens) => tokens.clone(),
+                None => vec![msg["to"].clone()],
+            })
+            .map(|token| json!({ "status": "ok", "id": token }))
+            .collect::<Vec<_>>();
+        TransportResponse::json(200, &json!({ "data": tickets }))
+    }))
+}
+
+#[tokio::test]
+async fn serializes_a_single_recipient_as_a_token() {
+    let transport = create_transport();
+    let client = ExpoNotificationsClient::new().transport(transport.clone());
+
+    let messages = [create_message("one", 1), create_message("two", 2)];
+    client.send_push_notifications(&messages).await.unwrap();
+
+    assert_eq!(
+        transport.requests()[0].json(),
+        json!([
+            { "to": token(0), "body": "one" },
+            { "to": [token(0), token(1)], "body": "two" }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn chunks_by_recipient_count() {
+    let transport = create_transport();
+    let client = ExpoNotificationsClient::new()
+        .transport(transport.clone())
+        .push_chunk_size(10);
+
+    let messages = [
+        create_message("a", 4),
+        create_message("b", 4),
+        create_message("c", 4),
+        create_message("d", 12),
+        create_message("e", 1),
+    ];
+    let result = client.send_push_notifications_batch(&messages).await;
+
+    let chunks = result
+        .chunks
+        .iter()
+        .map(|chunk| chunk.messages.clone())
+        .collect::<Vec<_>>();
+    // The 12 recipients of "d" are split into a full chunk and one shared with "e".
+    assert_eq!(chunks, [0..2, 2..3, 3..4, 3..5]);
+    let sizes = transport
+        .requests()
+        .iter()
+        .map(|req| {
+            let body = req.json();
+            let messages = body.as_array().unwrap();
+            messages
+                .iter()
+                .map(|msg| msg["to"].as_array().map_or(1, Vec::len))
+                .sum::<usize>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(sizes, [8, 4, 10, 3]);
+
+    // Every ticket is reported with the message and the token it was sent to.
+    let tickets = result.tickets().collect::<Vec<_>>();
+    assert_eq!(tickets.len(), 25);
+    for (index, token, ticket) in tickets {
+        assert!(messages[index].to.contains(token));
+        assert_eq!(
+            serde_json::to_value(ticket.receipt_id().unwrap()).unwrap(),
+            json!(token)
+        );
+    }
+}
+
+#[tokio::test]
+async fn keyed_tickets_are_reported_per_recipient() {
+    let client = ExpoNotificationsClient::new().transport(create_transport());
+
+    let messages = [
+        ("ada", create_message("a", 2)),
+        ("grace", create_message("b", 1)),
+    ];
+    let tickets = client
+        .send_push_notifications_keyed(messages)
+        .await
+        .unwrap();
+
+    let recipients = tickets
+        .iter()
+        .map(|(user, token, _)| (*user, token.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        recipients,
+        [("ada", token(0)), ("ada", token(1)), ("grace", token(0))]
+    );
+}
+
+#[tokio::test]
+async fn send_push_notification_needs_one_recipient() {
+    let transport = create_transport();
+    let client = ExpoNotificationsClient::new().transport(transport.clone());
+
+    let result = client.send_push_notification(&create_message("a", 2)).await;
+    assert!(matches!(
+        result,
+        Err(ExpoNotificationError::NotOneRecipient { count: 2 })
+    ));
+    assert!(transport.requests().is_empty());
+}
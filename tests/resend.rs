@@ -0,0 +1,215 @@
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use expo_server_sdk::{
+    error::ExpoNotificationError,
+    message::{PushMessage, PushToken},
+    response::{Outcome, PushTicket},
+    transport::{MockTransport, TransportResponse},
+    ExpoNotificationsClient, RetryPolicy,
+};
+use serde_json::json;
+
+const DELIVERED: &str = "ExponentPushToken[aaaaaaaaaaaaaaaaaaaaaa]";
+const THROTTLED_ONCE: &str = "ExponentPushToken[bbbbbbbbbbbbbbbbbbbbbb]";
+const THROTTLED: &str = "ExponentPushToken[cccccccccccccccccccccc]";
+const UNREGISTERED: &str = "ExponentPushToken[dddddddddddddddddddddd]";
+/// Like `THROTTLED_ONCE`, for another Expo project.
+const OTHER_PROJECT_THROTTLED_ONCE: &str = "ExponentPushToken[eeeeeeeeeeeeeeeeeeeeee]";
+
+fn token(token: &str) -> PushToken {
+    PushToken::from_str(token).unwrap()
+}
+
+fn create_transport() -> Arc<MockTransport> {
+    create_transport_refusing(|_, _| None)
+}
+
+/// A transport answering with `refuse`, given the number of the request and its tokens, or
+/// else with a ticket for each token.
+fn create_transport_refusing(
+    refuse: impl Fn(usize, &[String]) -> Option<TransportResponse> + Send + Sync + 'static,
+) -> Arc<MockTransport> {
+    let attempts = Mutex::new(HashMap::<String, u32>::new());
+    let requests = Mutex::new(0);
+    Arc::new(MockTransport::with_handler(move |req| {
+        let mut attempts = attempts.lock().unwrap();
+        let mut requests = requests.lock().unwrap();
+        *requests += 1;
+        let tokens = req
+            .json()
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|msg| match msg["to"].as_array() {
+                Some(tokens) => tokens.clone(),
+                None => vec![msg["to"].clone()],
+            })
+            .map(|to| to.as_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        if let Some(response) = refuse(*requests, &tokens) {
+            return response;
+        }
+        let tickets = tokens
+            .into_iter()
+            .map(|to| {
+                let attempt = attempts.entry(to.clone()).or_default();
+                *attempt += 1;
+                let error = match to.as_str() {
+                    THROTTLED_ONCE | OTHER_PROJECT_THROTTLED_ONCE if *attempt == 1 => {
+                        "MessageRateExceeded"
+                    }
+                    THROTTLED => "MessageRateExceeded",
+                    UNREGISTERED => "DeviceNotRegistered",
+                    _ => return json!({ "status": "ok", "id": to }),
+                };
+                json!({
+                    "status": "error",
+                    "message": error,
+                    "details": { "error": error, "expoPushToken": to }
+                })
+            })
+            .collect::<Vec<_>>();
+        TransportResponse::json(200, &json!({ "data": tickets }))
+    }))
+}
+
+fn resend_policy() -> RetryPolicy {
+    RetryPolicy::default()
+        .max_attempts(3)
+        .base_delay(Duration::from_millis(10))
+}
+
+#[tokio::test]
+async fn resends_retryable_tickets_only() {
+    let transport = create_transport();
+    let client = ExpoNotificationsClient::new()
+        .transport(transport.clone())
+        .resend_policy(Some(resend_policy()));
+
+    let messages = [
+        PushMessage::with_recipients([token(DELIVERED), token(THROTTLED_ONCE)]).body("a"),
+        PushMessage::new(token(THROTTLED)).body("b"),
+        PushMessage::new(token(UNREGISTERED)).body("c"),
+    ];
+    let result = client.send_push_notifications_batch(&messages).await;
+
+    let outcomes = result
+        .tickets()
+        .map(|(index, token, ticket)| (index, token.as_str(), ticket.outcome()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        outcomes,
+        [
+            (0, DELIVERED, Outcome::Delivered),
+            (0, THROTTLED_ONCE, Outcome::Delivered),
+            (1, THROTTLED, Outcome::RetryLater),
+            (2, UNREGISTERED, Outcome::DropToken),
+        ]
+    );
+    let failed = result
+        .failed_tickets()
+        .map(|(_, token, _)| token.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(failed, [THROTTLED, UNREGISTERED]);
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(
+        requests[1].json(),
+        json!([
+            { "to": THROTTLED_ONCE, "body": "a" },
+            { "to": THROTTLED, "body": "b" }
+        ])
+    );
+    assert_eq!(
+        requests[2].json(),
+        json!([{ "to": THROTTLED, "body": "b" }])
+    );
+}
+
+#[tokio::test]
+async fn does_not_resend_by_default() {
+    let transport = create_transport();
+    let client = ExpoNotificationsClient::new().transport(transport.clone());
+
+    let ticket = client
+        .send_push_notification(&PushMessage::new(token(THROTTLED)))
+        .await
+        .unwrap();
+    assert!(matches!(ticket, PushTicket::Error { .. }));
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn regroups_resends_by_project() {
+    // Refuses requests with tokens of both projects.
+    let transport = create_transport_refusing(|_, tokens| {
+        let mixed = tokens.iter().any(|to| to == THROTTLED_ONCE)
+            && tokens.iter().any(|to| to == OTHER_PROJECT_THROTTLED_ONCE);
+        mixed.then(|| {
+            TransportResponse::json(
+                400,
+                &json!({
+                    "errors": [{
+                        "code": "PUSH_TOO_MANY_EXPERIENCE_IDS",
+                        "message": "All push notification messages in the same request must be for the same project",
+                        "details": {
+                            "@alice/app": [THROTTLED_ONCE],
+                            "@bob/app": [OTHER_PROJECT_THROTTLED_ONCE]
+                        }
+                    }]
+                }),
+            )
+        })
+    });
+    let client = ExpoNotificationsClient::new()
+        .transport(transport.clone())
+        .resend_policy(Some(resend_policy()));
+
+    let message =
+        PushMessage::with_recipients([token(THROTTLED_ONCE), token(OTHER_PROJECT_THROTTLED_ONCE)]);
+    let tickets = client.send_push_notifications([message]).await.unwrap();
+
+    assert!(tickets
+        .iter()
+        .all(|ticket| ticket.outcome() == Outcome::Delivered));
+    // Both the first send and the resend are refused, then sent per project.
+    assert_eq!(transport.requests().len(), 6);
+}
+
+#[tokio::test]
+async fn reports_failed_resends() {
+    let transport = create_transport_refusing(|request, _| {
+        (request == 2).then(|| TransportResponse::status(400))
+    });
+    let client = ExpoNotificationsClient::new()
+        .transport(transport.clone())
+        .resend_policy(Some(resend_policy()));
+
+    let messages = [PushMessage::with_recipients([
+        token(DELIVERED),
+        token(THROTTLED),
+    ])];
+    let result = client.send_push_notifications_batch(&messages).await;
+
+    assert!(matches!(
+        result.chunks[0].result,
+        Err(ExpoNotificationError::PartiallySent { .. })
+    ));
+    let delivered = result
+        .tickets()
+        .map(|(_, token, _)| token.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(delivered, [DELIVERED]);
+    let failed = result
+        .failed_recipients()
+        .map(|(_, token)| token.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(failed, [THROTTLED]);
+    assert_eq!(transport.requests().len(), 2);
+}
@@ -0,0 +1,289 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    str::FromStr,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use expo_server_sdk::response::{
+    Fault, Outcome, PushErrorCode, PushReceipt, PushReceiptErrorDetails, PushReceiptId, PushTicket,
+    RequestError,
+};
+use serde_json::json;
+
+const TOKEN: &str = "ExponentPushToken[xxxxxxxxxxxxxxxxxxxxxx]";
+
+fn receipt_details(receipt: serde_json::Value) -> PushReceiptErrorDetails {
+    match serde_json::from_value(receipt).unwrap() {
+        PushReceipt::Error {
+            details: Some(details),
+            ..
+        } => details,
+        receipt => panic!("expected error details, got {receipt:?}"),
+    }
+}
+
+#[test]
+fn parses_provider_errors() {
+    let details = receipt_details(json!({
+        "status": "error",
+        "message": "The Apple Push Notification service rejected the notification",
+        "details": {
+            "error": "ProviderError",
+            "fault": "provider",
+            "apns": { "reason": "TooManyProviderTokenUpdates", "statusCode": 429 },
+            "sentAt": 1_700_000_000
+        }
+    }));
+
+    assert_eq!(details.code(), Some(PushErrorCode::ProviderError));
+    let PushReceiptErrorDetails::ProviderError { context } = details else {
+        panic!("expected a provider error, got {details:?}");
+    };
+    assert_eq!(context.fault, Some(Fault::Provider));
+    let apns = context.apns.unwrap();
+    assert_eq!(apns.reason.as_deref(), Some("TooManyProviderTokenUpdates"));
+    assert_eq!(apns.status_code, Some(429));
+    assert!(context.fcm.is_none());
+    assert_eq!(
+        context.sent_at,
+        Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+    );
+    assert!(context.extra.is_empty());
+}
+
+#[test]
+fn keeps_unknown_codes_and_fields() {
+    let details = receipt_details(json!({
+        "status": "error",
+        "message": "Something new happened",
+        "details": {
+            "error": "SomethingNew",
+            "fault": "somebody",
+            "retryAfter": 30,
+            "fcm": { "error": "QUOTA_EXCEEDED", "extraInfo": true }
+        }
+    }));
+
+    assert_eq!(
+        details.code(),
+        Some(PushErrorCode::Other("SomethingNew".to_owned()))
+    );
+    assert_eq!(details.fault(), Some(Fault::Other("somebody".to_owned())));
+    let PushReceiptErrorDetails::Unknown(details) = details else {
+        panic!("expected unknown details, got {details:?}");
+    };
+    assert_eq!(details["retryAfter"], json!(30));
+    assert_eq!(
+        details["fcm"],
+        json!({ "error": "QUOTA_EXCEEDED", "extraInfo": true })
+    );
+}
+
+#[test]
+fn keeps_provider_reason_and_error_apart() {
+    let mut receipts: HashMap<PushReceiptId, PushReceipt> = serde_json::from_value(json!({
+        "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX": {
+            "status": "error",
+            "message": "Firebase Cloud Messaging rejected the notification",
+            "details": {
+                "error": "ProviderError",
+                "fcm": { "error": "UNREGISTERED", "reason": "gone", "errorCode": "404" }
+            }
+        }
+    }))
+    .unwrap();
+
+    let receipt = receipts
+        .remove(&PushReceiptId::from_str("XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX").unwrap())
+        .unwrap();
+    let PushReceipt::Error {
+        details: Some(PushReceiptErrorDetails::ProviderError { context }),
+        ..
+    } = receipt
+    else {
+        panic!("expected a provider error, got {receipt:?}");
+    };
+    let fcm = context.fcm.unwrap();
+    assert_eq!(fcm.error.as_deref(), Some("UNREGISTERED"));
+    assert_eq!(fcm.reason.as_deref(), Some("gone"));
+    assert_eq!(fcm.error_code.as_deref(), Some("404"));
+}
+
+#[test]
+fn parses_unregistered_tokens() {
+    let ticket: PushTicket = serde_json::from_value(json!({
+        "status": "error",
+        "message": format!("\"{TOKEN}\" is not a registered push notification recipient"),
+        "details": { "error": "DeviceNotRegistered", "expoPushToken": TOKEN, "fault": "developer" }
+    }))
+    .unwrap();
+    assert_eq!(ticket.unregistered_token().unwrap().as_str(), TOKEN);
+
+    // Without the token, there is nothing to unregister, but the details are kept.
+    let ticket: PushTicket = serde_json::from_value(json!({
+        "status": "error",
+        "message": "not registered",
+        "details": { "error": "DeviceNotRegistered" }
+    }))
+    .unwrap();
+    assert!(ticket.unregistered_token().is_none());
+    assert_eq!(ticket.outcome(), Outcome::DropToken);
+    let PushTicket::Error {
+        details: Some(PushReceiptErrorDetails::Unknown(details)),
+        ..
+    } = ticket
+    else {
+        panic!("expected unknown details, got {ticket:?}");
+    };
+    assert_eq!(details["error"], json!("DeviceNotRegistered"));
+}
+
+fn receipt_outcome(details: serde_json::Value) -> Outcome {
+    let receipt: PushReceipt = serde_json::from_value(json!({
+        "status": "error",
+        "message": "failed",
+        "details": details
+    }))
+    .unwrap();
+    receipt.outcome()
+}
+
+#[test]
+fn classifies_outcomes() {
+    let ok: PushReceipt = serde_json::from_value(json!({ "status": "ok" })).unwrap();
+    assert_eq!(ok.outcome(), Outcome::Delivered);
+
+    let outcomes = [
+        ("DeviceNotRegistered", Outcome::DropToken),
+        ("InvalidCredentials", Outcome::FixConfig),
+        ("MismatchSenderId", Outcome::FixConfig),
+        ("MessageTooBig", Outcome::FixMessage),
+        ("DeveloperError", Outcome::FixMessage),
+        ("MessageRateExceeded", Outcome::RetryLater),
+        ("ProviderError", Outcome::RetryLater),
+        ("ExpoError", Outcome::RetryLater),
+    ];
+    for (code, outcome) in outcomes {
+        assert_eq!(receipt_outcome(json!({ "error": code })), outcome, "{code}");
+    }
+
+    // Unknown codes are classified by fault.
+    let unknown = |fault| receipt_outcome(json!({ "error": "SomethingNew", "fault": fault }));
+    assert_eq!(unknown("developer"), Outcome::FixMessage);
+    assert_eq!(unknown("expo"), Outcome::RetryLater);
+    assert_eq!(unknown("provider"), Outcome::RetryLater);
+    assert_eq!(unknown("somebody"), Outcome::FixMessage);
+}
+
+#[test]
+fn classifies_errors_without_information_alike() {
+    let without_details: PushReceipt =
+        serde_json::from_value(json!({ "status": "error", "message": "failed" })).unwrap();
+    assert_eq!(without_details.outcome(), Outcome::FixMessage);
+
+    assert_eq!(receipt_outcome(json!({})), Outcome::FixMessage);
+    assert_eq!(
+        receipt_outcome(json!({ "error": "SomethingNew" })),
+        Outcome::FixMessage
+    );
+    assert!(!receipt_outcome(json!({})).is_retryable());
+}
+
+#[test]
+fn classifies_ticket_outcomes() {
+    let ok: PushTicket = serde_json::from_value(json!({
+        "status": "ok",
+        "id": "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX"
+    }))
+    .unwrap();
+    assert_eq!(ok.outcome(), Outcome::Delivered);
+    assert!(!ok.outcome().is_retryable());
+
+    let rate_exceeded: PushTicket = serde_json::from_value(json!({
+        "status": "error",
+        "message": "too many messages",
+        "details": { "error": "MessageRateExceeded" }
+    }))
+    .unwrap();
+    assert!(rate_exceeded.outcome().is_retryable());
+
+    let without_details: PushTicket =
+        serde_json::from_value(json!({ "status": "error", "message": "failed" })).unwrap();
+    assert_eq!(without_details.outcome(), Outcome::FixMessage);
+    assert!(!without_details.outcome().is_retryable());
+}
+
+#[test]
+fn rebuilds_receipt_ids_from_strings() {
+    let id = "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX";
+    let parsed = PushReceiptId::from_str(id).unwrap();
+    assert_eq!(parsed, PushReceiptId::from(id.to_owned()));
+    assert_eq!(parsed, serde_json::from_value(json!(id)).unwrap());
+    assert_eq!(parsed.as_str(), id);
+    assert_eq!(parsed.to_string(), id);
+
+    let ids = ["b", "c", "a", "b"]
+        .map(|id| PushReceiptId::from_str(id).unwrap())
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+    let ids = ids.iter().map(PushReceiptId::as_str).collect::<Vec<_>>();
+    assert_eq!(ids, ["a", "b", "c"]);
+}
+
+/// Parse `data` from a recorded response, serialize it back and compare with the recording.
+fn assert_round_trips<T>(recorded: serde_json::Value)
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + Clone + PartialEq + std::fmt::Debug,
+{
+    let parsed: T = serde_json::from_value(recorded.clone()).unwrap();
+    assert_eq!(parsed.clone(), parsed);
+    assert_eq!(serde_json::to_value(&parsed).unwrap(), recorded);
+}
+
+#[test]
+fn round_trips_recorded_tickets() {
+    assert_round_trips::<Vec<PushTicket>>(json!([
+        { "status": "ok", "id": "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX" },
+        {
+            "status": "error",
+            "message": format!("\"{TOKEN}\" is not a registered push notification recipient"),
+            "details": { "error": "DeviceNotRegistered", "expoPushToken": TOKEN }
+        },
+        { "status": "error", "message": "The push notification service is unavailable" }
+    ]));
+}
+
+#[test]
+fn round_trips_recorded_receipts() {
+    assert_round_trips::<HashMap<PushReceiptId, PushReceipt>>(json!({
+        "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX": { "status": "ok" },
+        "YYYYYYYY-YYYY-YYYY-YYYY-YYYYYYYYYYYY": {
+            "status": "error",
+            "message": "The Apple Push Notification service failed to send the notification",
+            "details": {
+                "error": "ProviderError",
+                "fault": "provider",
+                "apns": { "reason": "TooManyProviderTokenUpdates", "statusCode": 429 },
+                "sentAt": 1_700_000_000,
+                "__debug": { "requestId": "abc" }
+            }
+        },
+        "ZZZZZZZZ-ZZZZ-ZZZZ-ZZZZ-ZZZZZZZZZZZZ": {
+            "status": "error",
+            "message": "Something new happened",
+            "details": { "error": "SomethingNew", "sentAt": 1_700_000_000.5 }
+        }
+    }));
+}
+
+#[test]
+fn round_trips_recorded_request_errors() {
+    assert_round_trips::<Vec<RequestError>>(json!([
+        {
+            "code": "PUSH_TOO_MANY_EXPERIENCE_IDS",
+            "message": "All push notification messages in the same request must be for the same project; check the details field to investigate conflicting tokens.",
+            "details": { "@alice/app": [TOKEN] }
+        },
+        { "code": "SOMETHING_NEW", "message": "A new request error" }
+    ]));
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use common::{Scripted, StandInServer, OK_TICKET};
+use expo_server_sdk::{
+    message::{PushMessage, PushToken},
+    response::PushReceiptId,
+    transport::{MockTransport, TransportResponse},
+    ExpoNotificationsClient, RetryPolicy,
+};
+use reqwest::header::{HeaderValue, RETRY_AFTER};
+use tokio::time::Instant;
+
+fn create_push_message() -> PushMessage {
+    PushMessage::new(PushToken::from_str("ExponentPushToken[xxxxxxxxxxxxxxxxxxxxxx]").unwrap())
+        .title("hello")
+}
+
+fn create_client(server: &StandInServer, retry_policy: RetryPolicy) -> ExpoNotificationsClient {
+    ExpoNotificationsClient::new()
+        .push_url(server.url("/push/send").parse().unwrap())
+        .retry_policy(retry_policy)
+}
+
+fn fast_retries(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy::default()
+        .max_attempts(max_attempts)
+        .base_delay(Duration::from_millis(10))
+        .jitter(0.0)
+}
+
+#[tokio::test]
+async fn does_not_retry_pushes_on_server_errors() {
+    let server = StandInServer::start([Scripted::status(502), Scripted::json(200, OK_TICKET)]);
+    let client = create_client(&server, fast_retries(3));
+
+    let result = client.send_push_notification(&create_push_message()).await;
+    assert!(result.is_err());
+    assert_eq!(server.request_count(), 1);
+}
+
+#[tokio::test]
+async fn retries_pushes_when_unavailable() {
+    let server = StandInServer::start([Scripted::status(503), Scripted::json(200, OK_TICKET)]);
+    let client = create_client(&server, fast_retries(3));
+
+    client
+        .send_push_notification(&create_push_message())
+        .await
+        .unwrap();
+    assert_eq!(server.request_count(), 2);
+}
+
+#[tokio::test]
+async fn retries_receipts_on_server_errors() {
+    let server = StandInServer::start([
+        Scripted::status(503),
+        Scripted::status(502),
+        Scripted::json(200, r#"{"data":{"XXXX":{"status":"ok"}}}"#),
+    ]);
+    let client = create_client(&server, fast_retries(3))
+        .receipt_url(server.url("/push/getReceipts").parse().unwrap());
+
+    let receipt = client
+        .get_push_receipt(&PushReceiptId::from("XXXX".to_string()))
+        .await
+        .unwrap();
+    assert!(receipt.is_some());
+    assert_eq!(server.request_count(), 3);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let server = StandInServer::start([
+        Scripted::status(429),
+        Scripted::status(429),
+        Scripted::json(200, OK_TICKET),
+    ]);
+    let client = create_client(&server, fast_retries(2));
+
+    let result = client.send_push_notification(&create_push_message()).await;
+    assert!(result.is_err());
+    assert_eq!(server.request_count(), 2);
+}
+
+#[tokio::test]
+async fn does_not_retry_client_errors() {
+    let server = StandInServer::start([Scripted::status(400), Scripted::json(200, OK_TICKET)]);
+    let client = create_client(&server, fast_retries(3));
+
+    let result = client.send_push_notification(&create_push_message()).await;
+    assert!(result.is_err());
+    assert_eq!(server.request_count(), 1);
+}
+
+#[tokio::test]
+async fn never_policy_does_not_retry() {
+    let server = StandInServer::start([Scripted::status(503), Scripted::json(200, OK_TICKET)]);
+    let client = create_client(&server, RetryPolicy::never());
+
+    let result = client.send_push_notification(&create_push_message()).await;
+    assert!(result.is_err());
+    assert_eq!(server.request_count(), 1);
+}
+
+#[tokio::test]
+async fn honors_retry_after() {
+    let server = StandInServer::start([
+        Scripted::status(429).header("retry-after", "0"),
+        Scripted::json(200, OK_TICKET),
+    ]);
+    // Without the header, the first retry would wait a minute.
+    let client = create_client(
+        &server,
+        fast_retries(2)
+            .base_delay(Duration::from_secs(60))
+            .max_delay(Duration::from_secs(60)),
+    );
+
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        client.send_push_notification(&create_push_message()),
+    )
+    .await
+    .expect("Retry-After should override the base delay")
+    .unwrap();
+    assert_eq!(server.request_count(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn waits_at_least_retry_after() {
+    let transport = Arc::new(MockTransport::new());
+    let mut too_many_requests = TransportResponse::status(429);
+    too_many_requests
+        .headers
+        .insert(RETRY_AFTER, HeaderValue::from_static("10"));
+    transport.push_response(too_many_requests);
+    transport.push_response(TransportResponse::json(
+        200,
+        &serde_json::from_str(OK_TICKET).unwrap(),
+    ));
+    // Neither the cap nor the jitter may bring the delay under what the server asked for.
+    let client = ExpoNotificationsClient::new()
+        .transport(transport.clone())
+        .retry_policy(
+            fast_retries(2)
+                .max_delay(Duration::from_secs(1))
+                .jitter(0.5),
+        );
+
+    let start = Instant::now();
+    client
+        .send_push_notification(&create_push_message())
+        .await
+        .unwrap();
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_secs(10), "{elapsed:?}");
+    assert!(elapsed <= Duration::from_secs(15), "{elapsed:?}");
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn clamps_out_of_range_jitter() {
+    let server = StandInServer::start([Scripted::status(429), Scripted::json(200, OK_TICKET)]);
+    let client = create_client(
+        &server,
+        RetryPolicy {
+            jitter: 5.0,
+            ..fast_retries(2)
+        },
+    );
+
+    client
+        .send_push_notification(&create_push_message())
+        .await
+        .unwrap();
+    assert_eq!(server.request_count(), 2);
+}
@@ -0,0 +1,55 @@
+//! The client futures must be `Send` so that they can run in spawned tasks, whether the input
+//! is borrowed or owned.
+
+mod common;
+
+use std::sync::Arc;
+
+use common::{create_push_messages, echo_transport};
+use expo_server_sdk::{response::PushReceiptId, ExpoNotificationsClient};
+
+#[tokio::test]
+async fn client_futures_can_be_spawned() {
+    let client = Arc::new(ExpoNotificationsClient::new().transport(echo_transport()));
+    let messages = create_push_messages(3);
+    let ids = ["0", "1", "2"].map(|id| PushReceiptId::from(id.to_owned()));
+
+    let tickets = {
+        let (client, messages) = (client.clone(), messages.clone());
+        tokio::spawn(async move { client.send_push_notifications(&messages).await })
+    };
+    assert_eq!(tickets.await.unwrap().unwrap().len(), 3);
+
+    let batch = {
+        let client = client.clone();
+        tokio::spawn(async move { client.send_push_notifications_batch(&messages).await })
+    };
+    assert!(batch.await.unwrap().is_complete());
+
+    let receipts = tokio::spawn(async move { client.get_push_receipts(&ids).await });
+    assert_eq!(receipts.await.unwrap().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn keyed_futures_can_be_spawned() {
+    let client = Arc::new(ExpoNotificationsClient::new().transport(echo_transport()));
+    let messages = create_push_messages(3);
+
+    let tickets = {
+        let client = client.clone();
+        tokio::spawn(async move {
+            client
+                .send_push_notifications_keyed(messages.into_iter().enumerate())
+                .await
+        })
+    };
+    let tickets = tickets.await.unwrap().unwrap();
+    assert_eq!(tickets.len(), 3);
+
+    let ids = tickets
+        .into_iter()
+        .map(|(key, _, ticket)| (key, ticket.receipt_id().unwrap().clone()))
+        .collect::<Vec<_>>();
+    let receipts = tokio::spawn(async move { client.get_push_receipts_keyed(ids).await });
+    assert_eq!(receipts.await.unwrap().unwrap().len(), 3);
+}
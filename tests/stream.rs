@@ -0,0 +1,75 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{create_push_messages, echo};
+use expo_server_sdk::{
+    transport::{MockTransport, TransportResponse},
+    ExpoNotificationsClient, RetryPolicy,
+};
+use futures_util::{stream, StreamExt};
+
+/// Answers with one ticket per message, except for the chunk starting with message 10.
+fn create_transport() -> Arc<MockTransport> {
+    Arc::new(MockTransport::with_handler(|req| {
+        if req.json()[0]["body"] == "10" {
+            TransportResponse::status(400)
+        } else {
+            echo(req)
+        }
+    }))
+}
+
+#[tokio::test]
+async fn yields_each_chunk_as_it_completes() {
+    let transport = create_transport();
+    let client = ExpoNotificationsClient::new()
+        .transport(transport.clone())
+        .retry_policy(RetryPolicy::never())
+        .push_chunk_size(10);
+
+    let results = client.send_push_notifications_stream(stream::iter(create_push_messages(25)));
+    futures_util::pin_mut!(results);
+
+    let first = results.next().await.unwrap();
+    assert_eq!(first.messages, 0..10);
+    assert_eq!(first.result.unwrap().len(), 10);
+    // Nothing more is sent until the next result is asked for.
+    assert_eq!(transport.requests().len(), 1);
+
+    let second = results.next().await.unwrap();
+    assert_eq!(second.messages, 10..20);
+    assert!(second.result.is_err());
+
+    let third = results.next().await.unwrap();
+    assert_eq!(third.messages, 20..25);
+    assert_eq!(third.result.unwrap().len(), 5);
+
+    assert!(results.next().await.is_none());
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn sends_messages_from_a_channel() {
+    let client = ExpoNotificationsClient::new()
+        .transport(create_transport())
+        .push_chunk_size(5);
+
+    let (tx, rx) = tokio::sync::mpsc::channel(3);
+    tokio::spawn(async move {
+        for msg in create_push_messages(7) {
+            tx.send(msg).await.unwrap();
+        }
+    });
+    let messages = stream::unfold(rx, |mut rx| async move {
+        let msg = rx.recv().await?;
+        Some((msg, rx))
+    });
+
+    let chunks = client
+        .send_push_notifications_stream(messages)
+        .map(|chunk| (chunk.messages, chunk.result.unwrap().len()))
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(chunks, [(0..5, 5), (5..7, 2)]);
+}
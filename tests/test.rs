@@ -78,19 +78,26 @@ mod tests {
 
     fn create_push_message() -> PushMessage {
         PushMessage {
-            to: PushToken::try_from(
+            to: vec![PushToken::try_from(
                 std::env::var("EXPO_SDK_RUST_TEST_PUSH_TOKEN")
                     .unwrap_or("ExponentPushToken[xxxxxxxxxxxxxxxxxxxxxx]".into()),
             )
-            .unwrap(),
+            .unwrap()],
             data: None,
             title: Some("hello".to_owned()),
+            subtitle: None,
             body: None,
             sound: Some(Sound::default()),
             ttl: None,
             expiration: None,
             priority: Some(Priority::default()),
             badge: None,
+            channel_id: None,
+            category_id: None,
+            mutable_content: None,
+            content_available: None,
+            interruption_level: None,
+            rich_content: None,
         }
     }
     fn create_client() -> ExpoNotificationsClient {
@@ -0,0 +1,76 @@
+use std::{str::FromStr, sync::Arc};
+
+use expo_server_sdk::{
+    message::{PushMessage, PushToken},
+    transport::{MockTransport, TransportResponse},
+    ExpoNotificationsClient, InMemoryTokenStore,
+};
+use serde_json::json;
+
+const ALIVE: &str = "ExponentPushToken[aaaaaaaaaaaaaaaaaaaaaa]";
+const DEAD: &str = "ExponentPushToken[dddddddddddddddddddddd]";
+
+fn token(token: &str) -> PushToken {
+    PushToken::from_str(token).unwrap()
+}
+
+fn device_not_registered() -> serde_json::Value {
+    json!({
+        "status": "error",
+        "message": format!("\"{DEAD}\" is not a registered push notification recipient"),
+        "details": { "error": "DeviceNotRegistered", "expoPushToken": DEAD }
+    })
+}
+
+fn create_store() -> Arc<InMemoryTokenStore> {
+    Arc::new(InMemoryTokenStore::new([token(ALIVE), token(DEAD)]))
+}
+
+#[tokio::test]
+async fn removes_tokens_from_tickets() {
+    let transport = MockTransport::new();
+    transport.push_response(TransportResponse::json(
+        200,
+        &json!({ "data": [{ "status": "ok", "id": "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX" }, device_not_registered()] }),
+    ));
+    let store = create_store();
+    let client = ExpoNotificationsClient::new()
+        .transport(transport)
+        .token_store(store.clone());
+
+    let messages = [
+        PushMessage::new(token(ALIVE)),
+        PushMessage::new(token(DEAD)),
+    ];
+    client.send_push_notifications(&messages).await.unwrap();
+
+    assert!(store.contains(&token(ALIVE)));
+    assert!(!store.contains(&token(DEAD)));
+}
+
+#[tokio::test]
+async fn removes_tokens_from_receipts() {
+    let transport = MockTransport::new();
+    transport.push_response(TransportResponse::json(
+        200,
+        &json!({ "data": {
+            "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX": { "status": "ok" },
+            "YYYYYYYY-YYYY-YYYY-YYYY-YYYYYYYYYYYY": device_not_registered()
+        } }),
+    ));
+    let store = create_store();
+    let client = ExpoNotificationsClient::new()
+        .transport(transport)
+        .token_store(store.clone());
+
+    let ids = [
+        "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX",
+        "YYYYYYYY-YYYY-YYYY-YYYY-YYYYYYYYYYYY",
+    ]
+    .map(|id| serde_json::from_value(json!(id)).unwrap());
+    let receipts = client.get_push_receipts(&ids).await.unwrap();
+
+    assert_eq!(receipts.len(), 2);
+    assert_eq!(store.len(), 1);
+    assert!(!store.contains(&token(DEAD)));
+}
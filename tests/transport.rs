@@ -0,0 +1,151 @@
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use expo_server_sdk::{
+    error::ExpoNotificationError,
+    message::{PushMessage, PushToken},
+    response::PushTicket,
+    transport::{MockTransport, Transport, TransportRequest, TransportResponse},
+    ExpoNotificationsClient, GzipPolicy, RetryPolicy,
+};
+use serde_json::json;
+
+fn create_push_message() -> PushMessage {
+    PushMessage::new(PushToken::from_str("ExponentPushToken[xxxxxxxxxxxxxxxxxxxxxx]").unwrap())
+        .title("hello")
+}
+
+fn ok_ticket() -> TransportResponse {
+    TransportResponse::json(
+        200,
+        &json!({ "data": [{ "status": "ok", "id": "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX" }] }),
+    )
+}
+
+#[tokio::test]
+async fn records_requests() {
+    let transport = Arc::new(MockTransport::new());
+    transport.push_response(ok_ticket());
+    let client = ExpoNotificationsClient::new()
+        .transport(transport.clone())
+        .authorization(Some("secret".to_owned()))
+        .gzip(GzipPolicy::Always);
+
+    let ticket = client
+        .send_push_notification(&create_push_message())
+        .await
+        .unwrap();
+    assert!(matches!(ticket, PushTicket::Ok { .. }));
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request.url.as_str(), "https://exp.host/--/api/v2/push/send");
+    assert_eq!(request.headers["authorization"], "Bearer secret");
+    assert_eq!(request.headers["content-encoding"], "gzip");
+    assert_eq!(
+        request.json(),
+        json!([{ "to": "ExponentPushToken[xxxxxxxxxxxxxxxxxxxxxx]", "title": "hello" }])
+    );
+}
+
+#[tokio::test]
+async fn retries_scripted_failures() {
+    let transport = Arc::new(MockTransport::new());
+    transport.push_response(TransportResponse::status(429));
+    transport.push_response(ok_ticket());
+    let client = ExpoNotificationsClient::new()
+        .transport(transport.clone())
+        .retry_policy(RetryPolicy::default().base_delay(Duration::from_millis(1)));
+
+    client
+        .send_push_notification(&create_push_message())
+        .await
+        .unwrap();
+    assert_eq!(transport.requests().len(), 2);
+}
+
+fn transport_error(transient: bool) -> ExpoNotificationError {
+    ExpoNotificationError::Transport {
+        source: "connection reset".into(),
+        transient,
+    }
+}
+
+#[tokio::test]
+async fn retries_transient_transport_errors() {
+    let transport = Arc::new(MockTransport::new());
+    transport.push_error(transport_error(true));
+    transport.push_response(ok_ticket());
+    let client = ExpoNotificationsClient::new()
+        .transport(transport.clone())
+        .retry_policy(RetryPolicy::default().base_delay(Duration::from_millis(1)));
+
+    client
+        .send_push_notification(&create_push_message())
+        .await
+        .unwrap();
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn returns_other_transport_errors() {
+    let transport = Arc::new(MockTransport::new());
+    transport.push_error(transport_error(false));
+    transport.push_response(ok_ticket());
+    let client = ExpoNotificationsClient::new()
+        .transport(transport.clone())
+        .retry_policy(RetryPolicy::default().base_delay(Duration::from_millis(1)));
+
+    let result = client.send_push_notification(&create_push_message()).await;
+    assert!(matches!(
+        result,
+        Err(ExpoNotificationError::Transport {
+            transient: false,
+            ..
+        })
+    ));
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn answers_with_handler() {
+    let transport = MockTransport::with_handler(|req| {
+        let ids = req.json()["ids"].clone();
+        let receipts = ids
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|id| (id.as_str().unwrap().to_owned(), json!({ "status": "ok" })))
+            .collect::<serde_json::Map<_, _>>();
+        TransportResponse::json(200, &json!({ "data": receipts }))
+    });
+    let client = ExpoNotificationsClient::new().transport(transport);
+
+    let id = serde_json::from_value(json!("XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX")).unwrap();
+    let receipt = client.get_push_receipt(&id).await.unwrap();
+    assert!(receipt.is_some());
+}
+
+/// A transport that is always down.
+struct Unreachable;
+
+#[async_trait]
+impl Transport for Unreachable {
+    async fn send(
+        &self,
+        _request: TransportRequest,
+    ) -> Result<TransportResponse, ExpoNotificationError> {
+        Err(ExpoNotificationError::Io(
+            std::io::ErrorKind::NotConnected.into(),
+        ))
+    }
+}
+
+#[tokio::test]
+async fn custom_transports() {
+    let client = ExpoNotificationsClient::new().transport(Unreachable);
+
+    let result = client.send_push_notification(&create_push_message()).await;
+    assert!(matches!(result, Err(ExpoNotificationError::Io(_))));
+}
@@ -0,0 +1,113 @@
+use std::{
+    str::FromStr,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use expo_server_sdk::{
+    error::ExpoNotificationError,
+    message::{MessageValidationError, PushMessage, PushToken, MAX_BADGE},
+    transport::MockTransport,
+    ExpoNotificationsClient,
+};
+use serde_json::json;
+
+fn create_push_message() -> PushMessage {
+    PushMessage::new(PushToken::from_str("ExponentPushToken[xxxxxxxxxxxxxxxxxxxxxx]").unwrap())
+}
+
+#[test]
+fn accepts_valid_messages() {
+    assert_eq!(create_push_message().body("hello").validate(), Ok(()));
+    assert_eq!(
+        create_push_message().data(json!({ "a": 1 })).validate(),
+        Ok(())
+    );
+    assert_eq!(
+        create_push_message().content_available(true).validate(),
+        Ok(())
+    );
+
+    let body = "x".repeat(4000);
+    assert_eq!(create_push_message().body(body).validate(), Ok(()));
+}
+
+#[test]
+fn reports_every_error() {
+    let message = PushMessage::with_recipients([])
+        .title("")
+        .data(json!(["not", "an", "object"]))
+        .ttl(60)
+        .expiration(1_700_000_000)
+        .badge(MAX_BADGE + 1);
+    assert_eq!(
+        message.validate(),
+        Err(vec![
+            MessageValidationError::NoRecipient,
+            MessageValidationError::DataNotAnObject,
+            MessageValidationError::TtlAndExpiration,
+            MessageValidationError::BadgeOutOfRange(MAX_BADGE + 1),
+        ])
+    );
+
+    assert_eq!(
+        create_push_message().validate(),
+        Err(vec![MessageValidationError::NoContent])
+    );
+
+    let message = create_push_message().body("x".repeat(4096));
+    assert!(matches!(
+        message.validate().unwrap_err()[..],
+        [MessageValidationError::PayloadTooBig { size }] if size > 4096
+    ));
+}
+
+#[tokio::test]
+async fn client_does_not_send_invalid_messages() {
+    let transport = MockTransport::new();
+    let client = ExpoNotificationsClient::new()
+        .transport(transport)
+        .validate_messages(true);
+
+    let messages = [create_push_message().body("hello"), create_push_message()];
+    let result = client.send_push_notifications(&messages).await;
+    assert!(matches!(
+        result,
+        Err(ExpoNotificationError::InvalidMessage { index: 1, ref errors })
+            if errors == &[MessageValidationError::NoContent]
+    ));
+}
+
+#[tokio::test]
+async fn invalid_messages_are_reported_at_their_position_in_the_input() {
+    let client = ExpoNotificationsClient::new()
+        .transport(MockTransport::with_handler(|_| unreachable!()))
+        .validate_messages(true)
+        .push_chunk_size(2);
+
+    let messages = vec![create_push_message(); 5];
+    let result = client.send_push_notifications_batch(&messages).await;
+    let indices = result
+        .chunks
+        .iter()
+        .map(|chunk| match chunk.result {
+            Err(ExpoNotificationError::InvalidMessage { index, .. }) => index,
+            _ => panic!("expected an invalid message"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(indices, [0, 2, 4]);
+}
+
+#[tokio::test]
+async fn expirations_before_the_epoch_are_not_sent() {
+    let message = create_push_message()
+        .body("hello")
+        .expires_at(UNIX_EPOCH - Duration::from_secs(1));
+    assert_eq!(
+        message.validate(),
+        Err(vec![MessageValidationError::ExpirationBeforeEpoch])
+    );
+
+    let client = ExpoNotificationsClient::new().transport(MockTransport::new());
+    let result = client.send_push_notification(&message).await;
+    assert!(matches!(result, Err(ExpoNotificationError::Serialize(_))));
+}